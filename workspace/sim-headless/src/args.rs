@@ -0,0 +1,55 @@
+use std::{
+    collections::HashMap,
+    str::FromStr,
+};
+
+pub struct Args {
+    values: HashMap<String, Vec<String>>,
+}
+
+impl Args {
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut values: HashMap<String, Vec<String>> = HashMap::new();
+        let mut args = args.peekable();
+
+        while let Some(arg) = args.next() {
+            let Some(key) = arg.strip_prefix("--") else {
+                return Err(format!("Unexpected argument: {}", arg));
+            };
+            let value = args.next_if(|next| !next.starts_with("--")).unwrap_or_default();
+            values.entry(key.to_string()).or_default().push(value);
+        }
+
+        Ok(Args { values })
+    }
+
+    pub fn get<T: FromStr>(&mut self, key: &str, default: T) -> Result<T, String> {
+        self.get_optional(key).map(|v| v.unwrap_or(default))
+    }
+
+    pub fn get_positive(&mut self, key: &str, default: f32) -> Result<f32, String> {
+        let value = self.get(key, default)?;
+        if !value.is_finite() || value <= 0. {
+            return Err(format!("--{} has to be finite and positive, got {}", key, value));
+        }
+        Ok(value)
+    }
+
+    pub fn get_optional<T: FromStr>(&mut self, key: &str) -> Result<Option<T>, String> {
+        match self.values.remove(key).and_then(|mut v| v.pop()) {
+            Some(value) => value.parse().map(Some).map_err(|_| format!("Invalid value for --{}: '{}'", key, value)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_all(&mut self, key: &str) -> Vec<String> {
+        self.values.remove(key).unwrap_or_default()
+    }
+
+    pub fn finish(self) -> Result<(), String> {
+        match self.values.keys().next() {
+            Some(key) => Err(format!("Unknown argument: --{}", key)),
+            None => Ok(()),
+        }
+    }
+}
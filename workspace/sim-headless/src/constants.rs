@@ -0,0 +1,7 @@
+pub const DEFAULT_CLUSTER_RADIUS: f32 = 0.5;
+pub const DEFAULT_CLUSTER_MIN_SIZE: usize = 5;
+pub const DEFAULT_WINNERS: usize = 3;
+pub const DEFAULT_PARTICLES_PER_SPECIES: usize = 300;
+pub const DEFAULT_TICKS: u32 = 500;
+pub const DEFAULT_RENDER_WIDTH: f32 = 1000.;
+pub const DEFAULT_TERMINAL_COLUMNS: f32 = 100.;
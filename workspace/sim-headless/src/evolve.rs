@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+use sim_lib::{ClusterCount, GeneticSearch, KineticEnergy, Motility, PhysicsMode, SearchConfig, ThreadPool};
+use crate::{args::Args, constants};
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let defaults = SearchConfig::default();
+    let physics_name: String = args.get("physics", defaults.physics_mode.name().to_string())?;
+
+    let config = SearchConfig {
+        population: args.get("population", defaults.population)?,
+        generations: args.get("generations", defaults.generations)?,
+        elite_count: args.get("elite", defaults.elite_count)?,
+        tournament_size: args.get("tournament", defaults.tournament_size)?,
+        particles_per_species: args.get("particles", defaults.particles_per_species)?,
+        ticks: args.get("ticks", defaults.ticks)?,
+        physics_mode: PhysicsMode::from_name(&physics_name).ok_or(format!("Unknown physics mode '{}'", physics_name))?,
+        min_force: args.get("min-force", defaults.min_force)?,
+        max_force: args.get("max-force", defaults.max_force)?,
+        mutation_rate: args.get("mutation-rate", defaults.mutation_rate)?,
+        mutation_strength: args.get("mutation-strength", defaults.mutation_strength)?,
+        seed: args.get("seed", defaults.seed)?,
+    };
+
+    let cluster_radius = args.get_positive("cluster-radius", constants::DEFAULT_CLUSTER_RADIUS)?;
+    let cluster_min_size = args.get("cluster-min-size", constants::DEFAULT_CLUSTER_MIN_SIZE)?;
+    let winners: usize = args.get("winners", constants::DEFAULT_WINNERS)?;
+    let out_dir: PathBuf = args.get("out", PathBuf::from("."))?;
+    let threads = args.get("threads", num_cpus::get())?;
+
+    let mut fitness_specs = args.get_all("fitness");
+    if fitness_specs.is_empty() {
+        fitness_specs.push("cluster".to_string());
+    }
+    args.finish()?;
+
+    let mut search = GeneticSearch::new(config)?;
+    for spec in fitness_specs {
+        let (name, weight) = match spec.split_once(':') {
+            Some((name, weight)) => (name, weight.parse().map_err(|_| format!("Invalid fitness weight in '{}'", spec))?),
+            None => (spec.as_str(), 1.),
+        };
+
+        search = match name {
+            "cluster" => search.with_objective(ClusterCount { radius: cluster_radius, min_size: cluster_min_size }, weight),
+            "motility" => search.with_objective(Motility, weight),
+            "energy" => search.with_objective(KineticEnergy, weight),
+            _ => return Err(format!("Unknown fitness function '{}'", name).into()),
+        };
+    }
+
+    let thread_pool = ThreadPool::new(threads);
+    let ranked = search.run(&thread_pool, |generation, ranked| {
+        let mean = ranked.iter().map(|c| c.score).sum::<f32>() / ranked.len() as f32;
+        println!("Generation {}: best {} mean {}", generation, ranked[0].score, mean);
+    });
+
+    std::fs::create_dir_all(&out_dir)?;
+    for (rank, candidate) in ranked.iter().take(winners).enumerate() {
+        let path = out_dir.join(format!("winner-{}.scenario", rank + 1));
+        candidate.to_scenario(search.config()).save(&path)?;
+        println!("Wrote {} (score {})", path.display(), candidate.score);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+mod args;
+mod constants;
+mod evolve;
+mod sweep;
+mod run;
+mod render;
+
+use args::Args;
+use sim_lib::{ForcesConfig, ParticleColor, PhysicsMode, Scenario};
+
+const USAGE: &str = "Usage: sim-headless <command> [--option value]...
+
+Commands:
+  evolve    Genetic search over force matrices, writes the winners as scenario files
+  run       Runs a scenario, optionally driven by a script, recording, saving or rendering the final state
+  sweep     Runs a grid of parameter combinations and writes one CSV row of metrics per run";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut argv = std::env::args().skip(1);
+    let command = argv.next().unwrap_or_default();
+    let args = Args::parse(argv)?;
+
+    match command.as_str() {
+        "evolve" => evolve::run(args),
+        "run" => run::run(args),
+        "sweep" => sweep::run(args),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+}
+
+fn default_scenario() -> Scenario {
+    ParticleColor::ALL.iter().fold(Scenario::new(PhysicsMode::Emergence, ForcesConfig::empty()), |s, &color| {
+        s.with_spawn(color, constants::DEFAULT_PARTICLES_PER_SPECIES)
+    })
+}
@@ -0,0 +1,32 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::Path,
+};
+use sim_lib::{World, WORLD_HEIGHT_FLOAT, WORLD_WIDTH_FLOAT};
+use sim_render::{Frame, FrameStyle, RasterRenderer, Renderer, SvgRenderer, TerminalRenderer, Viewport};
+use crate::constants;
+
+// `target` is either "terminal" or a file path whose extension picks the format, .svg or .ppm
+pub fn render_world(world: &World, target: &str, width: Option<f32>, style: &FrameStyle) -> Result<(), Box<dyn std::error::Error>> {
+    let frame = |default_width: f32| {
+        let width = width.unwrap_or(default_width);
+        Frame::styled(world.particles(), &Viewport::fit_world(width, (width * WORLD_HEIGHT_FLOAT / WORLD_WIDTH_FLOAT).round()), style)
+    };
+
+    if target == "terminal" {
+        return Ok(TerminalRenderer::new(std::io::stdout().lock()).render(&frame(constants::DEFAULT_TERMINAL_COLUMNS))?);
+    }
+
+    let frame = frame(constants::DEFAULT_RENDER_WIDTH);
+    match Path::new(target).extension().and_then(|e| e.to_str()) {
+        Some("svg") => SvgRenderer::new(BufWriter::new(File::create(target)?)).render(&frame)?,
+        Some("ppm") => {
+            let mut raster = RasterRenderer::new();
+            let Ok(()) = raster.render(&frame);
+            raster.write_ppm(BufWriter::new(File::create(target)?))?;
+        },
+        _ => return Err(format!("Don't know how to render to '{}', expected 'terminal', a .svg or a .ppm file", target).into()),
+    }
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+use sim_lib::{CalculationMethod, Recorder, Scenario, ThreadPool};
+use sim_script::ScriptHost;
+use sim_render::{ColorMode, FrameStyle, Trails};
+use crate::{args::Args, constants, render};
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let scenario = match args.get_optional::<PathBuf>("scenario")? {
+        Some(path) => Scenario::load(path)?,
+        None => crate::default_scenario(),
+    };
+
+    let ticks: u32 = args.get("ticks", constants::DEFAULT_TICKS)?;
+    let dt = args.get_positive("dt", 1.)?;
+    let seed: Option<u64> = args.get_optional("seed")?;
+    let threads = args.get("threads", num_cpus::get())?;
+    let script_path: Option<PathBuf> = args.get_optional("script")?;
+    let record_path: Option<PathBuf> = args.get_optional("record")?;
+    let record_interval = args.get("record-every", 1)?;
+    let save_path: Option<PathBuf> = args.get_optional("save")?;
+    let render_target: Option<String> = args.get_optional("render")?;
+    let render_width: Option<f32> = args.get_optional("render-width")?;
+    let color_mode: ColorMode = args.get("color-mode", ColorMode::default())?;
+    let arrows: bool = args.get("arrows", false)?;
+    let trail_length: Option<usize> = args.get_optional("trails")?;
+    args.finish()?;
+
+    let mut world = match seed {
+        Some(seed) => scenario.build_world_seeded(seed),
+        None => scenario.build_world(),
+    };
+
+    let calculation_method = if threads > 1 {
+        CalculationMethod::ThreadPool(ThreadPool::new(threads))
+    } else {
+        CalculationMethod::SingleThread
+    };
+
+    if let Some(path) = &record_path {
+        world.start_recording(Recorder::create(path, record_interval)?)?;
+    }
+
+    let mut script = script_path.map(ScriptHost::load).transpose()?;
+    if let Some(script) = script.as_mut() {
+        script.init(&mut world)?;
+    }
+
+    // Only the ticks that end up in the rendered trail are worth recording
+    let mut trails = trail_length.map(Trails::new);
+    let mut substeps: u64 = 0;
+    for tick in 0..ticks {
+        substeps += match script.as_mut() {
+            Some(script) => script.tick(&mut world, &calculation_method, dt)?,
+            None => world.tick(&calculation_method, dt),
+        } as u64;
+        world.health_issues().iter().for_each(|issue| eprintln!("{}", issue));
+        if let Some(trails) = trails.as_mut().filter(|t| ticks - tick <= t.length() as u32) {
+            trails.record(world.particles());
+        }
+    }
+
+    if let Some(mut recorder) = world.stop_recording()? {
+        recorder.flush()?;
+    }
+
+    if let Some(path) = &save_path {
+        Scenario::from_world(&world).save(path)?;
+    }
+
+    if let Some(target) = &render_target {
+        let style = FrameStyle::default()
+            .with_color_mode(color_mode)
+            .with_arrows(arrows)
+            .with_obstacles(world.obstacles())
+            .with_bonds(world.bonds())
+            .with_trails(trails.as_ref());
+        render::render_world(&world, target, render_width, &style)?;
+    }
+
+    println!("Ran {} ticks in {} substeps, {} particles", world.ticks(), substeps, world.particles().len());
+    for (reaction, count) in world.reactions().iter().zip(world.reaction_counts()) {
+        println!("{:?}: {} particle(s) changed", reaction, count);
+    }
+    Ok(())
+}
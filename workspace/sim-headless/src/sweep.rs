@@ -0,0 +1,86 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+use sim_lib::{ParticleColor, Scenario, Sweep, SweepAxis, ThreadPool};
+use crate::{args::Args, constants};
+
+pub fn run(mut args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let base = match args.get_optional::<PathBuf>("scenario")? {
+        Some(path) => Scenario::load(path)?,
+        None => crate::default_scenario(),
+    };
+
+    let ticks = args.get("ticks", constants::DEFAULT_TICKS)?;
+    let seeds: Vec<u64> = parse_list(&args.get("seeds", "0".to_string())?)?;
+    let cluster_radius = args.get_positive("cluster-radius", constants::DEFAULT_CLUSTER_RADIUS)?;
+    let cluster_min_size = args.get("cluster-min-size", constants::DEFAULT_CLUSTER_MIN_SIZE)?;
+    let threads = args.get("threads", num_cpus::get())?;
+    let out: Option<PathBuf> = args.get_optional("out")?;
+
+    let mut sweep = Sweep::new(base, ticks)
+        .with_seeds(seeds)
+        .with_cluster_params(cluster_radius, cluster_min_size);
+
+    for spec in args.get_all("force") {
+        let (pair, values) = spec.split_once('=').ok_or(format!("Expected <who>,<to>=<values> in '{}'", spec))?;
+        let (who, to) = pair.split_once(',').ok_or(format!("Expected <who>,<to> in '{}'", pair))?;
+        sweep = sweep.with_axis(SweepAxis::Force { who: parse_color(who)?, to: parse_color(to)?, values: parse_values(values)? });
+    }
+    for spec in args.get_all("friction") {
+        sweep = sweep.with_axis(SweepAxis::Friction(parse_values(&spec)?));
+    }
+    for spec in args.get_all("repel-radius") {
+        sweep = sweep.with_axis(SweepAxis::RepelRadius(parse_values(&spec)?));
+    }
+    for spec in args.get_all("temperature") {
+        sweep = sweep.with_axis(SweepAxis::Temperature(parse_values(&spec)?));
+    }
+    for spec in args.get_all("particles") {
+        sweep = sweep.with_axis(SweepAxis::ParticlesPerSpecies(parse_list(&spec)?));
+    }
+    args.finish()?;
+
+    let mut writer: Box<dyn Write> = match &out {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(std::io::stdout()),
+    };
+
+    let total = sweep.runs_count();
+    let thread_pool = ThreadPool::new(threads);
+    let mut result = sweep.write_csv_header(&mut writer);
+
+    sweep.run(&thread_pool, |run| {
+        if result.is_ok() {
+            result = sweep.write_csv_row(&mut writer, run).and_then(|_| writer.flush());
+        }
+        if out.is_some() {
+            eprintln!("Run {}/{} done", run.index + 1, total);
+        }
+    });
+
+    Ok(result?)
+}
+
+// Either an inclusive range "start:end:steps" or a comma separated list of values
+fn parse_values(spec: &str) -> Result<Vec<f32>, String> {
+    let parts: Vec<&str> = spec.split(':').collect();
+    match parts.as_slice() {
+        [start, end, steps] => Ok(SweepAxis::linspace(parse_value(start)?, parse_value(end)?, parse_value(steps)?)),
+        _ => parse_list(spec),
+    }
+}
+
+fn parse_list<T: FromStr>(spec: &str) -> Result<Vec<T>, String> {
+    spec.split(',').map(parse_value).collect()
+}
+
+fn parse_value<T: FromStr>(value: &str) -> Result<T, String> {
+    value.trim().parse().map_err(|_| format!("Invalid value '{}'", value))
+}
+
+fn parse_color(name: &str) -> Result<ParticleColor, String> {
+    ParticleColor::from_name(name.trim()).ok_or(format!("Unknown color '{}'", name))
+}
@@ -0,0 +1,84 @@
+// Source of gpu_kernel.ptx, the emergence law for `CalculationMethod::GPU`. Rebuild the PTX from this directory with
+//   nvcc -ptx -arch=sm_61 -o gpu_kernel.ptx gpu_kernel.cu
+// and keep the structs and the parameter list in step with src/gpu/interface.rs and `Executor`, nothing checks them.
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define NUM_COLORS 4
+#define PAIRS_COUNT (NUM_COLORS * NUM_COLORS)
+
+struct __attribute__((packed)) Constants {
+    float world_unit_size;
+    float force_scaler;
+    // Unused since the radii come per color pair in the forces buffer
+    float repel_force_radius;
+};
+
+struct __attribute__((packed)) Particle {
+    float position_x;
+    float position_y;
+    uint32_t color;
+};
+
+struct __attribute__((packed)) OutcomeAcceleration {
+    float acceleration_x;
+    float acceleration_y;
+};
+
+// Same as `emergence::calculate_force`, distances in world units
+__device__ float calculate_force(float configured_force, float distance, float repel, float interaction) {
+    if (distance <= repel) {
+        return distance / repel - 1.f;
+    } else if (distance < interaction) {
+        float numerator = fabsf(2.f * distance - interaction - repel);
+        return configured_force * (1.f - numerator / (interaction - repel));
+    }
+    return 0.f;
+}
+
+// One thread per particle, each sums the pull of every other particle on it. `forces` holds the strengths, then the
+// repel radii, then the interaction radii, PAIRS_COUNT floats each and indexed by who * NUM_COLORS + to.
+extern "C" __global__ void calculate_emergence_acceleration(
+    const Particle* particles,
+    size_t particles_len,
+    const Constants* constants,
+    const float* forces,
+    size_t forces_len,
+    OutcomeAcceleration* outcomes
+) {
+    size_t index = blockIdx.x * blockDim.x + threadIdx.x;
+    if (index >= particles_len) {
+        return;
+    }
+
+    Particle target = particles[index];
+    float acceleration_x = 0.f;
+    float acceleration_y = 0.f;
+    for (size_t i = 0; i < particles_len; i++) {
+        Particle other = particles[i];
+        float dx = other.position_x - target.position_x;
+        float dy = other.position_y - target.position_y;
+        // Also skips the target itself
+        float distance = sqrtf(dx * dx + dy * dy) / constants->world_unit_size;
+        if (distance == 0.f) {
+            continue;
+        }
+
+        size_t pair = target.color * NUM_COLORS + other.color;
+        if (pair + 2 * PAIRS_COUNT >= forces_len) {
+            __trap();
+        }
+        float force = calculate_force(forces[pair], distance, forces[PAIRS_COUNT + pair], forces[2 * PAIRS_COUNT + pair]);
+        if (force == 0.f) {
+            continue;
+        }
+
+        float length = sqrtf(dx * dx + dy * dy);
+        acceleration_x += dx / length * constants->force_scaler * force;
+        acceleration_y += dy / length * constants->force_scaler * force;
+    }
+
+    outcomes[index].acceleration_x = acceleration_x;
+    outcomes[index].acceleration_y = acceleration_y;
+}
@@ -0,0 +1,260 @@
+use std::{
+    f32::consts::PI,
+    io::{self, Write},
+};
+use crate::{
+    calc, Particle, ParticleColor, Point, World,
+    grid::SpatialGrid,
+    constants::{WORLD_HEIGHT_FLOAT, WORLD_UNIT_SIZE, WORLD_WIDTH_FLOAT},
+};
+
+const PAIRS_COUNT: usize = ParticleColor::matrix_len() * ParticleColor::matrix_len();
+
+// Pair correlation g(r) for every (who, to) species pair, with distances in world units as in `calc::acceleration_of`.
+// The world has hard edges, so only particles at least `max_distance` away from every edge serve as reference points.
+pub struct RadialDistribution {
+    max_distance: f32,
+    bins: usize,
+    histograms: Vec<Vec<f32>>,
+    reference_counts: [usize; ParticleColor::matrix_len()],
+    species_counts: [usize; ParticleColor::matrix_len()],
+}
+
+pub struct PairStatistics {
+    pub who: ParticleColor,
+    pub to: ParticleColor,
+    pub reference_count: usize,
+    pub coordination_number: f32,
+    pub peak_distance: f32,
+    pub peak_value: f32,
+}
+
+impl RadialDistribution {
+    pub fn compute(world: &World, max_distance: f32, bins: usize) -> Result<Self, String> {
+        if !max_distance.is_finite() || max_distance <= 0. || bins == 0 {
+            return Err(format!("Expected a positive distance and bins, got {} and {}", max_distance, bins));
+        }
+
+        let particles = &world.particles;
+        let max_distance_px = max_distance * WORLD_UNIT_SIZE;
+        let grid = SpatialGrid::new(particles.iter().map(|p| &p.position), max_distance_px);
+
+        let mut result = RadialDistribution {
+            max_distance,
+            bins,
+            histograms: vec![vec![0.; bins]; PAIRS_COUNT],
+            reference_counts: [0; ParticleColor::matrix_len()],
+            species_counts: [0; ParticleColor::matrix_len()],
+        };
+
+        particles.iter().for_each(|p| result.species_counts[p.color as usize] += 1);
+
+        for (i, reference) in particles.iter().enumerate() {
+            if !Self::is_reference_point(&reference.position, max_distance_px) {
+                continue;
+            }
+
+            result.reference_counts[reference.color as usize] += 1;
+
+            for j in grid.candidates_near(&reference.position, max_distance_px) {
+                if i == j {
+                    continue;
+                }
+
+                let distance = calc::world_distance(&reference.position, &particles[j].position);
+                if distance >= max_distance {
+                    continue;
+                }
+
+                let bin = usize::min((distance / result.bin_width()) as usize, bins - 1);
+                result.histograms[Self::pair_index(reference.color, particles[j].color)][bin] += 1.;
+            }
+        }
+
+        result.normalize();
+        Ok(result)
+    }
+
+    pub fn bins(&self) -> usize {
+        self.bins
+    }
+
+    pub fn bin_width(&self) -> f32 {
+        self.max_distance / self.bins as f32
+    }
+
+    pub fn bin_center(&self, bin: usize) -> f32 {
+        (bin as f32 + 0.5) * self.bin_width()
+    }
+
+    pub fn get(&self, who: ParticleColor, to: ParticleColor) -> &[f32] {
+        &self.histograms[Self::pair_index(who, to)]
+    }
+
+    pub fn pair_statistics(&self, who: ParticleColor, to: ParticleColor) -> PairStatistics {
+        let curve = self.get(who, to);
+        let density = self.neighbor_density(who, to);
+
+        let coordination_number = curve.iter().enumerate()
+            .map(|(bin, g)| g * density * self.shell_area(bin))
+            .sum();
+
+        let (peak_bin, peak_value) = curve.iter().enumerate()
+            .fold((0, 0.), |(best_bin, best), (bin, &g)| if g > best { (bin, g) } else { (best_bin, best) });
+
+        PairStatistics {
+            who,
+            to,
+            reference_count: self.reference_counts[who as usize],
+            coordination_number,
+            peak_distance: self.bin_center(peak_bin),
+            peak_value,
+        }
+    }
+
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "distance")?;
+        for &who in ParticleColor::ALL {
+            for &to in ParticleColor::ALL {
+                write!(writer, ",{}-{}", who.name(), to.name())?;
+            }
+        }
+        writeln!(writer)?;
+
+        for bin in 0..self.bins {
+            write!(writer, "{}", self.bin_center(bin))?;
+            for &who in ParticleColor::ALL {
+                for &to in ParticleColor::ALL {
+                    write!(writer, ",{}", self.get(who, to)[bin])?;
+                }
+            }
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_pair_statistics_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "who,to,reference_count,coordination_number,peak_distance,peak_value")?;
+
+        for &who in ParticleColor::ALL {
+            for &to in ParticleColor::ALL {
+                let stats = self.pair_statistics(who, to);
+                writeln!(
+                    writer,
+                    "{},{},{},{},{},{}",
+                    stats.who.name(),
+                    stats.to.name(),
+                    stats.reference_count,
+                    stats.coordination_number,
+                    stats.peak_distance,
+                    stats.peak_value
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn normalize(&mut self) {
+        for &who in ParticleColor::ALL {
+            for &to in ParticleColor::ALL {
+                let references = self.reference_counts[who as usize] as f32;
+                let density = self.neighbor_density(who, to);
+                let pair_index = Self::pair_index(who, to);
+
+                for bin in 0..self.bins {
+                    let expected = references * density * self.shell_area(bin);
+                    let observed = &mut self.histograms[pair_index][bin];
+                    *observed = if expected > 0. { *observed / expected } else { 0. };
+                }
+            }
+        }
+    }
+
+    fn neighbor_density(&self, who: ParticleColor, to: ParticleColor) -> f32 {
+        let world_area = (WORLD_WIDTH_FLOAT / WORLD_UNIT_SIZE) * (WORLD_HEIGHT_FLOAT / WORLD_UNIT_SIZE);
+        let neighbors = self.species_counts[to as usize].saturating_sub(if who == to { 1 } else { 0 });
+        neighbors as f32 / world_area
+    }
+
+    fn shell_area(&self, bin: usize) -> f32 {
+        let inner = bin as f32 * self.bin_width();
+        let outer = inner + self.bin_width();
+        PI * (outer.powi(2) - inner.powi(2))
+    }
+
+    fn is_reference_point(pos: &Point, margin: f32) -> bool {
+        pos.x >= margin && pos.x <= WORLD_WIDTH_FLOAT - margin && pos.y >= margin && pos.y <= WORLD_HEIGHT_FLOAT - margin
+    }
+
+    fn pair_index(who: ParticleColor, to: ParticleColor) -> usize {
+        who as usize * ParticleColor::matrix_len() + to as usize
+    }
+}
+
+pub fn mean_kinetic_energy(particles: &[Particle]) -> f32 {
+    if particles.is_empty() {
+        return 0.;
+    }
+    particles.iter().map(|p| 0.5 * p.velocity.square_length()).sum::<f32>() / particles.len() as f32
+}
+
+pub fn mean_displacement(initial: &[Particle], current: &[Particle]) -> f32 {
+    let count = usize::min(initial.len(), current.len());
+    if count == 0 {
+        return 0.;
+    }
+    initial.iter().zip(current.iter())
+        .map(|(a, b)| calc::world_distance(&a.position, &b.position))
+        .sum::<f32>() / count as f32
+}
+
+// Particles closer than `radius` world units are linked; clusters smaller than `min_size` are not counted.
+pub fn cluster_count(particles: &[Particle], radius: f32, min_size: usize) -> usize {
+    let mut sizes = vec![0usize; particles.len()];
+    cluster_labels(particles, radius).into_iter().for_each(|label| sizes[label] += 1);
+    sizes.into_iter().filter(|&size| size > 0 && size >= min_size).count()
+}
+
+// Same linking as `cluster_count`. Particles in the same cluster get the same label, labels are particle indices.
+pub fn cluster_labels(particles: &[Particle], radius: f32) -> Vec<usize> {
+    assert!(radius > 0.);
+
+    let radius_px = radius * WORLD_UNIT_SIZE;
+    let grid = SpatialGrid::new(particles.iter().map(|p| &p.position), radius_px);
+    let mut parents: Vec<usize> = (0..particles.len()).collect();
+
+    for (i, p) in particles.iter().enumerate() {
+        for j in grid.candidates_near(&p.position, radius_px) {
+            if j > i && calc::world_distance(&p.position, &particles[j].position) < radius {
+                let (root_i, root_j) = (find_root(&mut parents, i), find_root(&mut parents, j));
+                parents[root_i] = root_j;
+            }
+        }
+    }
+
+    (0..particles.len()).map(|i| find_root(&mut parents, i)).collect()
+}
+
+// Number of other particles within `radius` world units of each particle
+pub fn local_density(particles: &[Particle], radius: f32) -> Vec<usize> {
+    assert!(radius > 0.);
+
+    let radius_px = radius * WORLD_UNIT_SIZE;
+    let grid = SpatialGrid::new(particles.iter().map(|p| &p.position), radius_px);
+
+    particles.iter().enumerate().map(|(i, p)| {
+        grid.candidates_near(&p.position, radius_px)
+            .filter(|&j| j != i && calc::world_distance(&p.position, &particles[j].position) < radius)
+            .count()
+    }).collect()
+}
+
+fn find_root(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
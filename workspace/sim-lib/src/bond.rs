@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+use crate::{
+    Particle, ParticleColor, calc,
+    constants::{FORCE_SCALAR, WORLD_UNIT_SIZE},
+    grid::SpatialGrid,
+};
+
+// Strain is relative to the rest length, so bonds never rest shorter than this, in world units
+const MIN_REST_LENGTH: f32 = 0.01;
+
+// A Hookean spring between the particles at indices `a` and `b`. Lengths are in world units like the pairwise
+// forces, `stiffness` is the pull per world unit of stretch.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bond {
+    pub a: usize,
+    pub b: usize,
+    pub rest_length: f32,
+    pub stiffness: f32,
+    // Breaks once stretched or squeezed by more than this fraction of the rest length
+    pub max_strain: Option<f32>,
+}
+
+// Bends the bonds `center` - `a` and `center` - `b` toward `angle` radians apart, PI keeps a chain straight.
+// Dropped together with either bond.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AngleConstraint {
+    pub a: usize,
+    pub center: usize,
+    pub b: usize,
+    pub angle: f32,
+    pub stiffness: f32,
+}
+
+// Bonds a `who` and a `to` particle that come closer than `distance` world units, resting at the distance they
+// bonded at, as long as neither already has `valence` bonds
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BondRule {
+    pub who: ParticleColor,
+    pub to: ParticleColor,
+    pub distance: f32,
+    pub stiffness: f32,
+    pub valence: usize,
+    pub max_strain: Option<f32>,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Bonds {
+    pub(crate) bonds: Vec<Bond>,
+    pub(crate) angles: Vec<AngleConstraint>,
+    pub(crate) rules: Vec<BondRule>,
+}
+
+impl Bond {
+    pub fn new(a: usize, b: usize, rest_length: f32, stiffness: f32) -> Self {
+        Bond { a, b, rest_length, stiffness, max_strain: None }
+    }
+
+    pub fn with_max_strain(mut self, max_strain: f32) -> Self {
+        self.max_strain = Some(max_strain);
+        self
+    }
+
+    fn strain(&self, particles: &[Particle]) -> f32 {
+        let distance = calc::world_distance(&particles[self.a].position, &particles[self.b].position);
+        let rest_length = self.rest_length.max(MIN_REST_LENGTH);
+        f32::abs(distance - rest_length) / rest_length
+    }
+
+    fn joins(&self, i: usize, j: usize) -> bool {
+        (self.a == i && self.b == j) || (self.a == j && self.b == i)
+    }
+}
+
+impl Bonds {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bonds.is_empty() && self.angles.is_empty() && self.rules.is_empty()
+    }
+
+    pub(crate) fn apply(&self, particles: &mut [Particle], dt: f32) {
+        for bond in self.bonds.iter() {
+            let offset = (particles[bond.b].position - particles[bond.a].position) / WORLD_UNIT_SIZE;
+            let Some(direction) = offset.try_normalize() else {
+                continue;
+            };
+            let acceleration = direction * (bond.stiffness * (offset.length() - bond.rest_length) * FORCE_SCALAR * dt);
+            particles[bond.a].velocity += acceleration;
+            particles[bond.b].velocity -= acceleration;
+        }
+
+        for constraint in self.angles.iter() {
+            let center = particles[constraint.center].position;
+            let (u, v) = ((particles[constraint.a].position - center) / WORLD_UNIT_SIZE, (particles[constraint.b].position - center) / WORLD_UNIT_SIZE);
+            let (Some(u_direction), Some(v_direction)) = (u.try_normalize(), v.try_normalize()) else {
+                continue;
+            };
+            let cos = u_direction.dot(v_direction).clamp(-1., 1.);
+            let error = cos.acos() - constraint.angle;
+
+            // Each end is pushed sideways to its bond, toward the other end when the angle is too wide. Undefined
+            // when both bonds line up, which is only ever the target for straight chains.
+            let (Some(toward_b), Some(toward_a)) = ((v_direction - u_direction * cos).try_normalize(), (u_direction - v_direction * cos).try_normalize()) else {
+                continue;
+            };
+            let a_acceleration = toward_b * (constraint.stiffness * error / u.length() * FORCE_SCALAR * dt);
+            let b_acceleration = toward_a * (constraint.stiffness * error / v.length() * FORCE_SCALAR * dt);
+            particles[constraint.a].velocity += a_acceleration;
+            particles[constraint.b].velocity += b_acceleration;
+            particles[constraint.center].velocity -= a_acceleration + b_acceleration;
+        }
+    }
+
+    pub(crate) fn break_overstrained(&mut self, particles: &[Particle]) {
+        let broken: Vec<Bond> = self.bonds.iter()
+            .filter(|bond| bond.max_strain.is_some_and(|max_strain| bond.strain(particles) > max_strain))
+            .copied()
+            .collect();
+        if broken.is_empty() {
+            return;
+        }
+
+        self.bonds.retain(|bond| !broken.contains(bond));
+        self.angles.retain(|c| !broken.iter().any(|bond| bond.joins(c.a, c.center) || bond.joins(c.b, c.center)));
+    }
+
+    pub(crate) fn form(&mut self, particles: &[Particle]) {
+        let Some(max_distance) = self.rules.iter().map(|rule| rule.distance).reduce(f32::max) else {
+            return;
+        };
+
+        let mut bonded: HashSet<(usize, usize)> = self.bonds.iter().map(|bond| (bond.a.min(bond.b), bond.a.max(bond.b))).collect();
+        let mut counts = vec![0; particles.len()];
+        self.bonds.iter().for_each(|bond| {
+            counts[bond.a] += 1;
+            counts[bond.b] += 1;
+        });
+
+        let grid = SpatialGrid::new(particles.iter().map(|p| &p.position), max_distance * WORLD_UNIT_SIZE);
+        for (i, particle) in particles.iter().enumerate() {
+            for j in grid.candidates_near(&particle.position, max_distance * WORLD_UNIT_SIZE) {
+                if j == i || bonded.contains(&(i.min(j), i.max(j))) {
+                    continue;
+                }
+
+                let distance = calc::world_distance(&particle.position, &particles[j].position);
+                let rule = self.rules.iter().find(|rule| {
+                    rule.who == particle.color && rule.to == particles[j].color
+                        && distance < rule.distance && counts[i] < rule.valence && counts[j] < rule.valence
+                });
+                if let Some(rule) = rule {
+                    self.bonds.push(Bond { a: i, b: j, rest_length: distance.max(MIN_REST_LENGTH), stiffness: rule.stiffness, max_strain: rule.max_strain });
+                    bonded.insert((i.min(j), i.max(j)));
+                    counts[i] += 1;
+                    counts[j] += 1;
+                }
+            }
+        }
+    }
+
+    // Drops whatever involves particles past the end, for when the particles are replaced wholesale
+    pub(crate) fn truncate(&mut self, particles_count: usize) {
+        self.bonds.retain(|bond| bond.a < particles_count && bond.b < particles_count);
+        self.angles.retain(|c| c.a < particles_count && c.center < particles_count && c.b < particles_count);
+    }
+}
+
+// `new_index[i]` is where particle `i` moved to, None if it is gone along with everything attached to it
+pub(crate) fn remap(bonds: &mut Vec<Bond>, angles: &mut Vec<AngleConstraint>, new_index: &[Option<usize>]) {
+    let moved = |i: usize| new_index.get(i).copied().flatten();
+    *bonds = bonds.iter()
+        .filter_map(|bond| Some(Bond { a: moved(bond.a)?, b: moved(bond.b)?, ..*bond }))
+        .collect();
+    *angles = angles.iter()
+        .filter_map(|c| Some(AngleConstraint { a: moved(c.a)?, center: moved(c.center)?, b: moved(c.b)?, ..*c }))
+        .collect();
+}
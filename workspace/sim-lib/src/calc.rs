@@ -1,12 +1,12 @@
 use crate::{
-    Particle, ForcesConfig, PhysicsMode, Vector, constants, physics,
+    ForceLaw, Particle, ForcesConfig, PairForce, PhysicsParams, Vector, constants,
     def::{Point, WorldEdge},
     constants::{WORLD_HEIGHT_FLOAT, WORLD_WIDTH_FLOAT}
 };
 use rand::Rng;
 
-pub fn acceleration_of(p_target: &Particle, p_other: &Particle, forces: &ForcesConfig, physics_mode: PhysicsMode) -> Option<Vector> {
-    let distance = p_target.position.distance_to(p_other.position) / constants::WORLD_UNIT_SIZE;
+pub fn acceleration_of(p_target: &Particle, p_other: &Particle, forces: &ForcesConfig, law: &PairForce, params: &PhysicsParams) -> Option<Vector> {
+    let distance = world_distance(&p_target.position, &p_other.position);
 
     if distance == 0. {
         return None;
@@ -14,10 +14,8 @@ pub fn acceleration_of(p_target: &Particle, p_other: &Particle, forces: &ForcesC
 
     let configured_force = forces.get(p_target.color, p_other.color);
 
-    let force = match physics_mode {
-        PhysicsMode::Emergence => physics::emergence::calculate_force(configured_force, distance),
-        PhysicsMode::Real => physics::real::calculate_force(configured_force, distance),
-    };
+    let radii = forces.radii(p_target.color, p_other.color, params);
+    let force = law.force(distance, configured_force, p_target.color, p_other.color, radii);
 
     if force == 0. {
         return None;
@@ -27,8 +25,13 @@ pub fn acceleration_of(p_target: &Particle, p_other: &Particle, forces: &ForcesC
     Some(Vector::from_angle_and_length(direction_vec.angle_from_x_axis(), force * constants::FORCE_SCALAR))
 }
 
+pub fn world_distance(a: &Point, b: &Point) -> f32 {
+    a.distance_to(*b) / constants::WORLD_UNIT_SIZE
+}
+
+// None for NaN positions too, there is no edge to bounce those off
 pub fn check_out_of_bounds(pos: &Point) -> Option<WorldEdge> {
-    if !is_out_of_bounds(pos) {
+    if !is_out_of_bounds(pos) || pos.x.is_nan() || pos.y.is_nan() {
         return None;
     }
 
@@ -59,12 +62,16 @@ pub fn check_out_of_bounds(pos: &Point) -> Option<WorldEdge> {
     Some(curr.0)
 }
 
+// NaN positions are nowhere in the world, so they count as out of it
 pub fn is_out_of_bounds(pos: &Point) -> bool {
-    pos.x > WORLD_WIDTH_FLOAT || pos.x < 0. || pos.y > WORLD_HEIGHT_FLOAT || pos.y < 0.
+    !(0. ..=WORLD_WIDTH_FLOAT).contains(&pos.x) || !(0. ..=WORLD_HEIGHT_FLOAT).contains(&pos.y)
 }
 
 pub fn random_world_position() -> Point {
-    let mut rng = rand::rng();
+    random_world_position_with(&mut rand::rng())
+}
+
+pub fn random_world_position_with<R: Rng + ?Sized>(rng: &mut R) -> Point {
     Point::new(rng.random_range(0f32..WORLD_WIDTH_FLOAT), rng.random_range(0f32..WORLD_HEIGHT_FLOAT))
 }
 
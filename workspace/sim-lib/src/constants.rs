@@ -1,6 +1,5 @@
 pub const WORLD_UNIT_SIZE: f32 = 100.0;
 pub const FORCE_SCALAR: f32 = 0.3;
-pub const BASE_PARTICLE_RADIUS: f32 = 3.0;
 pub const WORLD_WIDTH: u32 = 4000;
 pub const WORLD_HEIGHT: u32 = 2000;
 pub const WORLD_WIDTH_FLOAT: f32 = WORLD_WIDTH as f32;
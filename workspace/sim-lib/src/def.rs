@@ -1,14 +1,20 @@
-use femtovg::Color;
 use rand::Rng;
+use crate::{PhysicsParams, physics::emergence};
 
 pub type Vector = euclid::default::Vector2D<f32>;
 pub type Point = euclid::default::Point2D<f32>;
 
+// Fixed layout so bindings can hand out strided views over position and velocity without copying
 #[derive(Copy, Clone)]
+#[repr(C)]
 pub struct Particle {
     pub(crate) position: Point,
     pub(crate) velocity: Vector,
     pub(crate) color: ParticleColor,
+    // Pinned particles exert forces like any other but never move
+    pub(crate) pinned: bool,
+    // Ticks since the particle last changed species
+    pub(crate) age: u64,
 }
 
 #[derive(Eq, PartialEq)]
@@ -19,42 +25,92 @@ pub enum WorldEdge {
     Top,
 }
 
-impl Into<Color> for ParticleColor {
-    fn into(self) -> Color {
-        match self {
-            ParticleColor::Blue => Color::rgb(0, 0, 255),
-            ParticleColor::Green => Color::rgb(0, 255, 0),
-            ParticleColor::Red => Color::rgb(255, 0, 0),
-            ParticleColor::Yellow => Color::rgb(252, 186, 3),
-        }
-    }
-}
-
 impl Particle {
+    pub const POSITION_OFFSET: usize = std::mem::offset_of!(Particle, position);
+    pub const VELOCITY_OFFSET: usize = std::mem::offset_of!(Particle, velocity);
+
     pub fn new(position: Point, velocity: Vector, color: ParticleColor) -> Self {
         Particle {
             position,
             velocity,
-            color
+            color,
+            pinned: false,
+            age: 0,
         }
     }
+
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    pub fn velocity(&self) -> Vector {
+        self.velocity
+    }
+
+    pub fn color(&self) -> ParticleColor {
+        self.color
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.pinned
+    }
+
+    pub fn age(&self) -> u64 {
+        self.age
+    }
+
+    pub fn with_position(mut self, position: Point) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_velocity(mut self, velocity: Vector) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn with_color(mut self, color: ParticleColor) -> Self {
+        if color != self.color {
+            self.age = 0;
+        }
+        self.color = color;
+        self
+    }
+
+    pub fn with_pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
 }
 
 #[derive(Copy, Clone)]
 pub struct ForcesConfig {
-    matrix: [[f32; ParticleColor::matrix_len()]; ParticleColor::matrix_len()]
+    matrix: [[f32; ParticleColor::matrix_len()]; ParticleColor::matrix_len()],
+    // Pairs without their own radii use the global repel radius and an interaction radius of 1
+    radii: [[Option<PairRadii>; ParticleColor::matrix_len()]; ParticleColor::matrix_len()],
+}
+
+// In world units, `who` is pushed away from `to` inside `repel` and pulled toward it up to `interaction`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PairRadii {
+    pub repel: f32,
+    pub interaction: f32,
 }
 
 impl ForcesConfig {
     pub fn empty() -> Self {
         ForcesConfig {
-            matrix: [[0.; ParticleColor::matrix_len()]; ParticleColor::matrix_len()]
+            matrix: [[0.; ParticleColor::matrix_len()]; ParticleColor::matrix_len()],
+            radii: [[None; ParticleColor::matrix_len()]; ParticleColor::matrix_len()],
         }
     }
 
     pub fn random(min_force: f32, max_force: f32) -> Self {
+        Self::random_with(&mut rand::rng(), min_force, max_force)
+    }
+
+    pub fn random_with<R: Rng + ?Sized>(rng: &mut R, min_force: f32, max_force: f32) -> Self {
         let max_force = f32::abs(max_force);
-        let mut rng = rand::rng();
         (0..ParticleColor::matrix_len()*ParticleColor::matrix_len()).fold(Self::empty(), |c, i| {
             c.with_force_unchecked(i / ParticleColor::matrix_len(), i % ParticleColor::matrix_len(), rng.random_range(min_force..max_force))
         })
@@ -69,6 +125,15 @@ impl ForcesConfig {
         self
     }
 
+    pub fn with_radii(self, who: ParticleColor, to: ParticleColor, radii: PairRadii) -> Self {
+        self.with_radii_unchecked(who as usize, to as usize, Some(radii))
+    }
+
+    pub fn with_radii_unchecked(mut self, who: usize, to: usize, radii: Option<PairRadii>) -> Self {
+        self.radii[who][to] = radii;
+        self
+    }
+
     pub fn get(&self, who: ParticleColor, to: ParticleColor) -> f32 {
         self.get_unchecked(who as usize, to as usize)
     }
@@ -76,6 +141,22 @@ impl ForcesConfig {
     pub fn get_unchecked(&self, who: usize, to: usize) -> f32 {
         self.matrix[who][to]
     }
+
+    // Only the radii set for this pair, see `radii` for what is actually used
+    pub fn get_radii(&self, who: ParticleColor, to: ParticleColor) -> Option<PairRadii> {
+        self.radii[who as usize][to as usize]
+    }
+
+    pub fn radii(&self, who: ParticleColor, to: ParticleColor, params: &PhysicsParams) -> PairRadii {
+        self.radii_unchecked(who as usize, to as usize, params)
+    }
+
+    pub fn radii_unchecked(&self, who: usize, to: usize, params: &PhysicsParams) -> PairRadii {
+        self.radii[who][to].unwrap_or(PairRadii {
+            repel: params.repel_radius,
+            interaction: emergence::INTERACTION_RADIUS,
+        })
+    }
 }
 
 macro_rules! define_particle_color {
@@ -84,7 +165,7 @@ macro_rules! define_particle_color {
             $($variant:ident = $val:expr),+ $(,)?
         }
     ) => {
-        #[derive(Copy, Clone)]
+        #[derive(Copy, Clone, Debug, Eq, PartialEq)]
         #[repr(usize)]
         pub enum $name {
             $($variant = $val),+
@@ -92,6 +173,8 @@ macro_rules! define_particle_color {
 
         impl $name {
             #![allow(unused_comparisons)]
+            pub const ALL: &'static [$name] = &[$($name::$variant),+];
+
             pub const fn max_value() -> usize {
                 let mut max = 0;
                 $(
@@ -99,6 +182,16 @@ macro_rules! define_particle_color {
                 )+
                 max
             }
+
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $($name::$variant => stringify!($variant)),+
+                }
+            }
+
+            pub fn from_name(name: &str) -> Option<Self> {
+                Self::ALL.iter().copied().find(|v| v.name().eq_ignore_ascii_case(name))
+            }
         }
     };
 }
@@ -114,6 +207,6 @@ define_particle_color! {
 
 impl ParticleColor {
     pub const fn matrix_len() -> usize {
-        return (ParticleColor::max_value()) + 1;
+        ParticleColor::max_value() + 1
     }
 }
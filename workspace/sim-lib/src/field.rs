@@ -0,0 +1,134 @@
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use crate::{
+    Particle, Point, Vector,
+    constants::{WORLD_HEIGHT_FLOAT, WORLD_UNIT_SIZE, WORLD_WIDTH_FLOAT},
+};
+
+// Anything that pushes particles around regardless of other particles. The returned acceleration is sampled
+// again every substep and added to the particle's velocity scaled by the substep length, after friction and the
+// pairwise forces.
+pub trait ExternalField: Send + Sync {
+    fn acceleration(&self, particle: &Particle) -> Vector;
+}
+
+// The built-in fields, plus `Custom` for user implementations. Distances are in world units, like the pairwise
+// forces, accelerations in pixels per tick per tick.
+#[derive(Clone)]
+pub enum Field {
+    // Same pull everywhere
+    Gravity { acceleration: Vector },
+    // Pulls toward `center` (pushes away when `strength` is negative), softened so it stays finite at the center
+    Attractor { center: Point, strength: f32, softening: f32 },
+    // Swirls around `center`, strongest at `radius` away from it. Positive strength turns clockwise on screen.
+    Vortex { center: Point, strength: f32, radius: f32 },
+    // Pushes along x in proportion to the distance from the horizontal middle of the world
+    Shear { rate: f32 },
+    // Sampled from a file, see `VectorGrid`
+    Grid { path: PathBuf, grid: Arc<VectorGrid>, scale: f32 },
+    Custom(Arc<dyn ExternalField>),
+}
+
+// Accelerations sampled on a regular grid stretched over the whole world, bilinearly interpolated in between.
+// File format: "<columns> <rows>" followed by columns * rows "<ax> <ay>" pairs, row by row from the top left.
+pub struct VectorGrid {
+    columns: usize,
+    rows: usize,
+    values: Vec<Vector>,
+}
+
+impl Field {
+    pub fn grid<P: AsRef<Path>>(path: P, scale: f32) -> io::Result<Self> {
+        Ok(Field::Grid {
+            path: path.as_ref().to_path_buf(),
+            grid: Arc::new(VectorGrid::load(path)?),
+            scale,
+        })
+    }
+
+    pub fn custom<F: ExternalField + 'static>(field: F) -> Self {
+        Field::Custom(Arc::new(field))
+    }
+}
+
+impl ExternalField for Field {
+    fn acceleration(&self, particle: &Particle) -> Vector {
+        let position = particle.position;
+        match self {
+            Field::Gravity { acceleration } => *acceleration,
+            Field::Attractor { center, strength, softening } => {
+                let offset = (*center - position) / WORLD_UNIT_SIZE;
+                let distance_squared = offset.square_length() + softening * softening;
+                if distance_squared == 0. {
+                    return Vector::zero();
+                }
+                offset * (strength / (distance_squared * distance_squared.sqrt()))
+            },
+            Field::Vortex { center, strength, radius } => {
+                let offset = (position - *center) / WORLD_UNIT_SIZE;
+                let distance_squared = offset.square_length() + radius * radius;
+                if distance_squared == 0. {
+                    return Vector::zero();
+                }
+                Vector::new(-offset.y, offset.x) * (strength / distance_squared)
+            },
+            Field::Shear { rate } => Vector::new(rate * (position.y - WORLD_HEIGHT_FLOAT / 2.) / WORLD_UNIT_SIZE, 0.),
+            Field::Grid { grid, scale, .. } => grid.sample(position) * *scale,
+            Field::Custom(field) => field.acceleration(particle),
+        }
+    }
+}
+
+impl VectorGrid {
+    pub fn new(columns: usize, rows: usize, values: Vec<Vector>) -> Result<Self, String> {
+        if columns == 0 || rows == 0 || columns.checked_mul(rows) != Some(values.len()) {
+            return Err(format!("Expected {} x {} values, got {}", columns, rows, values.len()));
+        }
+        Ok(VectorGrid { columns, rows, values })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let text = fs::read_to_string(path)?;
+        let mut words = text.split_whitespace();
+        let (Some(columns), Some(rows)) = (words.next(), words.next()) else {
+            return Err(invalid("Vector grid is missing its size".to_string()));
+        };
+        let size = |n: &str| n.parse::<usize>().ok().filter(|&n| n > 0)
+            .ok_or_else(|| invalid(format!("Invalid vector grid size '{}'", n)));
+        let (columns, rows) = (size(columns)?, size(rows)?);
+        let expected = columns.checked_mul(rows).and_then(|count| count.checked_mul(2))
+            .ok_or_else(|| invalid(format!("Vector grid of {} x {} is too big", columns, rows)))?;
+
+        let numbers = words
+            .map(|n| n.parse::<f32>().map_err(|_| invalid(format!("Invalid number '{}' in vector grid", n))))
+            .collect::<io::Result<Vec<f32>>>()?;
+        if numbers.len() != expected {
+            return Err(invalid(format!("Expected {} x {} values, got {} numbers", columns, rows, numbers.len())));
+        }
+        let values = numbers.chunks(2).map(|xy| Vector::new(xy[0], xy[1])).collect();
+        Self::new(columns, rows, values).map_err(invalid)
+    }
+
+    pub fn sample(&self, position: Point) -> Vector {
+        // Cell centers sit on the sample points, positions outside the world take the nearest edge value
+        let x = (position.x / WORLD_WIDTH_FLOAT * self.columns as f32 - 0.5).clamp(0., (self.columns - 1) as f32);
+        let y = (position.y / WORLD_HEIGHT_FLOAT * self.rows as f32 - 0.5).clamp(0., (self.rows - 1) as f32);
+        if x.is_nan() || y.is_nan() {
+            return Vector::zero();
+        }
+
+        let (column, row) = (x as usize, y as usize);
+        let (next_column, next_row) = (usize::min(column + 1, self.columns - 1), usize::min(row + 1, self.rows - 1));
+        let (tx, ty) = (x - column as f32, y - row as f32);
+        let value = |column: usize, row: usize| self.values[row * self.columns + column];
+
+        let top = value(column, row).lerp(value(next_column, row), tx);
+        let bottom = value(column, next_row).lerp(value(next_column, next_row), tx);
+        top.lerp(bottom, ty)
+    }
+}
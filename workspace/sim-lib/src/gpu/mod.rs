@@ -1,14 +1,17 @@
 use cust::memory::DeviceBox;
 use cust::prelude::*;
-use crate::{ForcesConfig, Particle, Vector, ParticleColor as CrateParticleColor};
+use crate::{ForcesConfig, Particle, PhysicsParams, Vector, ParticleColor as CrateParticleColor};
 use interface::ParticleColor as InterfaceParticleColor;
 
 mod interface;
 
+// Compiled from resources/gpu_kernel.cu, its header has the command that rebuilds it
 static PTX: &str = include_str!("../../resources/gpu_kernel.ptx");
 static PTX_KERNEL_NAME: &str = "calculate_emergence_acceleration";
 
-const FORCES_CONFIG_ARRAY_LEN: usize = interface::NUM_COLORS * interface::NUM_COLORS;
+const PAIRS_COUNT: usize = interface::NUM_COLORS * interface::NUM_COLORS;
+// Strengths, then repel radii, then interaction radii
+const FORCES_CONFIG_ARRAY_LEN: usize = PAIRS_COUNT * 3;
 const _: () = assert!(interface::NUM_COLORS == CrateParticleColor::matrix_len());
 
 const ZERO_ACCELERATION: interface::OutcomeAcceleration = interface::OutcomeAcceleration {
@@ -22,25 +25,37 @@ pub struct Executor {
     stream: Stream,
 }
 
+impl Default for Executor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Executor {
     pub fn new() -> Self {
-        Executor {
-            _context: cust::quick_init().unwrap(),
-            module: Module::from_ptx(PTX, &[]).unwrap(),
-            stream: Stream::new(StreamFlags::NON_BLOCKING, None).unwrap(),
-        }
+        Self::try_new().unwrap()
+    }
+
+    // Fails when there is no CUDA device to run on
+    pub fn try_new() -> Result<Self, String> {
+        let context = cust::quick_init().map_err(|e| format!("Can't set up CUDA: {}", e))?;
+        Ok(Executor {
+            _context: context,
+            module: Module::from_ptx(PTX, &[]).map_err(|e| format!("Can't load the kernel: {}", e))?,
+            stream: Stream::new(StreamFlags::NON_BLOCKING, None).map_err(|e| format!("Can't create a stream: {}", e))?,
+        })
     }
 
-    pub(crate) fn calculate_emergence_accelerations(&self, particles: &[Particle], forces_config: &ForcesConfig) -> Vec<Vector> {
+    pub(crate) fn calculate_emergence_accelerations(&self, particles: &[Particle], forces_config: &ForcesConfig, params: &PhysicsParams) -> Vec<Vector> {
         let mut outcomes = vec![ZERO_ACCELERATION; particles.len()];
         let outcomes_gpu = outcomes.as_slice().as_dbuf().unwrap();
-        let constants_gpu = Self::alloc_gpu_constants();
-        let forces_gpu = Self::alloc_gpu_forces_config(&forces_config);
+        let constants_gpu = Self::alloc_gpu_constants(params);
+        let forces_gpu = Self::alloc_gpu_forces_config(forces_config, params);
         let particles_gpu = Self::alloc_particles_gpu(particles);
 
         let func = self.module.get_function(PTX_KERNEL_NAME).unwrap();
         let (_, block_size) = func.suggested_launch_configuration(0, 0.into()).unwrap();
-        let grid_size = (particles.len() as u32 + block_size - 1) / block_size;
+        let grid_size = (particles.len() as u32).div_ceil(block_size);
 
         let stream = &self.stream;
 
@@ -63,20 +78,24 @@ impl Executor {
         outcomes.iter().map(|acc_gpu| Vector::new(acc_gpu.acceleration_x, acc_gpu.acceleration_y)).collect()
     }
 
-    fn alloc_gpu_constants() -> DeviceBox<interface::Constants> {
+    fn alloc_gpu_constants(params: &PhysicsParams) -> DeviceBox<interface::Constants> {
         let c = interface::Constants {
             world_unit_size: crate::constants::WORLD_UNIT_SIZE,
             force_scaler: crate::constants::FORCE_SCALAR,
-            repel_force_radius: crate::physics::emergence::GLOBAL_REPEL_FORCE_RADIUS,
+            repel_force_radius: params.repel_radius,
         };
 
         c.as_dbox().unwrap()
     }
 
-    fn alloc_gpu_forces_config(forces_config: &ForcesConfig) -> DeviceBuffer<f32> {
+    fn alloc_gpu_forces_config(forces_config: &ForcesConfig, params: &PhysicsParams) -> DeviceBuffer<f32> {
         let mut arr = [0.0f32; FORCES_CONFIG_ARRAY_LEN];
-        (0..FORCES_CONFIG_ARRAY_LEN).for_each(|i| {
-            arr[i] = forces_config.get_unchecked(i / interface::NUM_COLORS, i % interface::NUM_COLORS);
+        (0..PAIRS_COUNT).for_each(|i| {
+            let (who, to) = (i / interface::NUM_COLORS, i % interface::NUM_COLORS);
+            let radii = forces_config.radii_unchecked(who, to, params);
+            arr[i] = forces_config.get_unchecked(who, to);
+            arr[PAIRS_COUNT + i] = radii.repel;
+            arr[PAIRS_COUNT * 2 + i] = radii.interaction;
         });
         arr.as_slice().as_dbuf().unwrap()
     }
@@ -95,9 +114,9 @@ impl Executor {
     }
 }
 
-impl Into<InterfaceParticleColor> for CrateParticleColor {
-    fn into(self) -> InterfaceParticleColor {
-        match self {
+impl From<CrateParticleColor> for InterfaceParticleColor {
+    fn from(color: CrateParticleColor) -> Self {
+        match color {
             CrateParticleColor::Blue => InterfaceParticleColor::Blue,
             CrateParticleColor::Red => InterfaceParticleColor::Red,
             CrateParticleColor::Green => InterfaceParticleColor::Green,
@@ -0,0 +1,60 @@
+use crate::{
+    Point,
+    constants::{WORLD_HEIGHT_FLOAT, WORLD_WIDTH_FLOAT},
+};
+
+// Smaller cells would only save distance checks, at the cost of memory growing with the square of the count
+const MAX_CELLS_PER_SIDE: f32 = 1024.;
+
+pub(crate) struct SpatialGrid {
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl SpatialGrid {
+    // Cells can come out bigger than `cell_size`, never smaller, so the candidates still cover every radius up to it
+    pub fn new<'a>(positions: impl Iterator<Item = &'a Point>, cell_size: f32) -> Self {
+        let cell_size = cell_size.max(WORLD_WIDTH_FLOAT.max(WORLD_HEIGHT_FLOAT) / MAX_CELLS_PER_SIDE);
+        let columns = (WORLD_WIDTH_FLOAT / cell_size).ceil().max(1.) as usize;
+        let rows = (WORLD_HEIGHT_FLOAT / cell_size).ceil().max(1.) as usize;
+
+        let mut grid = SpatialGrid {
+            cell_size,
+            columns,
+            rows,
+            cells: vec![Vec::new(); columns * rows],
+        };
+
+        positions.enumerate().for_each(|(i, pos)| {
+            let (column, row) = grid.cell_of(pos);
+            grid.cells[row * grid.columns + column].push(i);
+        });
+
+        grid
+    }
+
+    pub fn candidates_near(&self, pos: &Point, radius: f32) -> impl Iterator<Item = usize> + '_ {
+        let (min_column, min_row) = self.cell_of(&Point::new(pos.x - radius, pos.y - radius));
+        let (max_column, max_row) = self.cell_of(&Point::new(pos.x + radius, pos.y + radius));
+
+        (min_row..=max_row).flat_map(move |row| {
+            (min_column..=max_column).flat_map(move |column| self.cells[row * self.columns + column].iter().copied())
+        })
+    }
+
+    fn cell_of(&self, pos: &Point) -> (usize, usize) {
+        let column = Self::bounded_index(pos.x / self.cell_size, self.columns);
+        let row = Self::bounded_index(pos.y / self.cell_size, self.rows);
+        (column, row)
+    }
+
+    fn bounded_index(value: f32, len: usize) -> usize {
+        if value.is_nan() || value < 0. {
+            0
+        } else {
+            usize::min(value as usize, len - 1)
+        }
+    }
+}
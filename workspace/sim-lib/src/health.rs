@@ -0,0 +1,80 @@
+use std::fmt::{self, Display};
+use crate::{Particle, Point, Vector};
+
+// What to do with particles whose position or velocity stopped being finite
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum HealthPolicy {
+    Panic,
+    // Stops the particle, and puts it somewhere random if it lost its position too
+    Repair,
+    Remove,
+}
+
+// Where in the tick a particle first went bad
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TickStage {
+    // Already bad when the tick started, set from outside
+    BeforeTick,
+    // Friction and the pair forces
+    PairForces,
+    Fields,
+    Bonds,
+    Thermostat,
+    // Moving by the velocity, edges and obstacles included
+    Motion,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HealthIssue {
+    // Index at the time it was found, still the particle's index unless it was removed
+    pub particle: usize,
+    pub tick: u64,
+    pub stage: TickStage,
+    pub position: Point,
+    pub velocity: Vector,
+    // Dropped by the remove policy, the particles after it moved down a place
+    pub removed: bool,
+}
+
+impl HealthPolicy {
+    pub const fn name(self) -> &'static str {
+        match self {
+            HealthPolicy::Panic => "panic",
+            HealthPolicy::Repair => "repair",
+            HealthPolicy::Remove => "remove",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [HealthPolicy::Panic, HealthPolicy::Repair, HealthPolicy::Remove].into_iter().find(|p| p.name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl TickStage {
+    pub const fn name(self) -> &'static str {
+        match self {
+            TickStage::BeforeTick => "before tick",
+            TickStage::PairForces => "pair forces",
+            TickStage::Fields => "fields",
+            TickStage::Bonds => "bonds",
+            TickStage::Thermostat => "thermostat",
+            TickStage::Motion => "motion",
+        }
+    }
+}
+
+impl Display for HealthIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Particle {} went non-finite on tick {}, stage: {} (position {}, {}, velocity {}, {}){}",
+            self.particle, self.tick, self.stage.name(), self.position.x, self.position.y, self.velocity.x, self.velocity.y,
+            if self.removed { ", removed" } else { "" }
+        )
+    }
+}
+
+pub(crate) fn is_healthy(particle: &Particle) -> bool {
+    particle.position.x.is_finite() && particle.position.y.is_finite()
+        && particle.velocity.x.is_finite() && particle.velocity.y.is_finite()
+}
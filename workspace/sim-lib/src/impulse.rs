@@ -0,0 +1,56 @@
+use std::f32::consts::TAU;
+use rand::Rng;
+use crate::{Particle, ParticleColor, Point, Vector, constants::WORLD_UNIT_SIZE};
+
+// A one-off change to the velocities, in pixels per tick. Pinned particles never move, and a particle whose
+// velocity would stop being finite is left as it was.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Impulse {
+    // Speeds particles up along the way they are already going, particles at rest in a random direction
+    Boost { amount: f32 },
+    // Adds `strength` in a random direction to every particle
+    Kick { strength: f32 },
+    // Pushes away from `center`, `strength` right at it fading to nothing `radius` world units away. Particles
+    // sitting right at the center fly off in a random direction.
+    Explosion { center: Point, strength: f32, radius: f32 },
+}
+
+impl Impulse {
+    // `only` limits it to one species
+    pub(crate) fn apply<R: Rng + ?Sized>(&self, particles: &mut [Particle], only: Option<ParticleColor>, rng: &mut R) {
+        let affected = particles.iter_mut().filter(|p| !p.pinned && only.is_none_or(|color| p.color == color));
+        for particle in affected {
+            let Some(change) = self.change_for(particle, rng) else {
+                continue;
+            };
+            let velocity = particle.velocity + change;
+            if velocity.x.is_finite() && velocity.y.is_finite() {
+                particle.velocity = velocity;
+            }
+        }
+    }
+
+    fn change_for<R: Rng + ?Sized>(&self, particle: &Particle, rng: &mut R) -> Option<Vector> {
+        match *self {
+            Impulse::Boost { amount } => {
+                let direction = particle.velocity.try_normalize().unwrap_or_else(|| random_direction(rng));
+                Some(direction * amount)
+            },
+            Impulse::Kick { strength } => Some(random_direction(rng) * strength),
+            Impulse::Explosion { center, strength, radius } => {
+                let offset = (particle.position - center) / WORLD_UNIT_SIZE;
+                let distance = offset.length();
+                if distance >= radius {
+                    return None;
+                }
+                let direction = offset.try_normalize().unwrap_or_else(|| random_direction(rng));
+                Some(direction * (strength * (1. - distance / radius)))
+            },
+        }
+    }
+}
+
+fn random_direction<R: Rng + ?Sized>(rng: &mut R) -> Vector {
+    let (sin, cos) = (TAU * rng.random::<f32>()).sin_cos();
+    Vector::new(cos, sin)
+}
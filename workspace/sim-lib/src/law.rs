@@ -0,0 +1,123 @@
+use std::sync::Arc;
+use crate::{PairRadii, ParticleColor, PhysicsMode, calc, physics};
+
+// Built-ins other than the original two are bounded to the size of the emergence repulsion, anything stiffer
+// flings particles across the world in one tick
+const MAX_FORCE: f32 = 1.;
+// Lennard-Jones is cut off this many sigmas away, where it is down to under 2% of its deepest pull
+const LENNARD_JONES_CUTOFF: f32 = 2.5;
+
+// How strongly `who` is pulled toward `to` at `distance` world units, negative pushes it away. `coefficient` and
+// `radii` are what the forces config holds for `who` -> `to`, laws are free to ignore the radii.
+pub trait ForceLaw: Send + Sync {
+    fn force(&self, distance: f32, coefficient: f32, who: ParticleColor, to: ParticleColor, radii: PairRadii) -> f32;
+}
+
+// The built-in laws, plus `Custom` for user implementations. Lengths are in world units.
+#[derive(Clone)]
+pub enum PairForce {
+    // Inverse square softened over `softening`, what `PhysicsMode::Real` uses by default
+    Real { softening: f32 },
+    // Repulsion inside the repel radius and a triangular pull up to the interaction radius, what
+    // `PhysicsMode::Emergence` uses by default
+    Emergence,
+    // Coefficient is the well depth, particles settle `sigma` * 2^(1/6) apart
+    LennardJones { sigma: f32 },
+    // Coefficient is the well depth, `width` how quickly the pull fades past `equilibrium`
+    Morse { equilibrium: f32, width: f32 },
+    // Inverse square screened off over `screening`, coefficient attracts like in Real
+    Yukawa { screening: f32 },
+    // Coefficient is the stiffness, nothing is felt beyond `range`
+    Spring { rest_length: f32, range: f32 },
+    Custom(Arc<dyn ForceLaw>),
+}
+
+impl PairForce {
+    pub fn for_mode(physics_mode: PhysicsMode) -> Self {
+        match physics_mode {
+            PhysicsMode::Real => PairForce::Real { softening: physics::real::DEFAULT_SOFTENING },
+            PhysicsMode::Emergence => PairForce::Emergence,
+        }
+    }
+
+    pub fn custom<L: ForceLaw + 'static>(law: L) -> Self {
+        PairForce::Custom(Arc::new(law))
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            PairForce::Real { .. } => "real",
+            PairForce::Emergence => "emergence",
+            PairForce::LennardJones { .. } => "lennard-jones",
+            PairForce::Morse { .. } => "morse",
+            PairForce::Yukawa { .. } => "yukawa",
+            PairForce::Spring { .. } => "spring",
+            PairForce::Custom(_) => "custom",
+        }
+    }
+
+    // Parses a law name followed by its parameters, as written by `parameters`
+    pub fn from_parts(name: &str, values: &[f32]) -> Result<Self, String> {
+        let law = match (name.to_ascii_lowercase().as_str(), values) {
+            // Scenarios written before the softening was configurable
+            ("real", []) => PairForce::Real { softening: physics::real::DEFAULT_SOFTENING },
+            ("real", &[softening]) => PairForce::Real { softening },
+            ("emergence", []) => PairForce::Emergence,
+            ("lennard-jones", &[sigma]) => PairForce::LennardJones { sigma },
+            ("morse", &[equilibrium, width]) => PairForce::Morse { equilibrium, width },
+            ("yukawa", &[screening]) => PairForce::Yukawa { screening },
+            ("spring", &[rest_length, range]) => PairForce::Spring { rest_length, range },
+            ("real" | "emergence" | "lennard-jones" | "morse" | "yukawa" | "spring", _) => {
+                return Err(format!("Wrong number of parameters for force law '{}'", name));
+            },
+            _ => return Err(format!("Unknown force law '{}'", name)),
+        };
+        Ok(law)
+    }
+
+    pub fn parameters(&self) -> Vec<f32> {
+        match self {
+            PairForce::Emergence | PairForce::Custom(_) => Vec::new(),
+            PairForce::Real { softening } => vec![*softening],
+            PairForce::LennardJones { sigma } => vec![*sigma],
+            PairForce::Morse { equilibrium, width } => vec![*equilibrium, *width],
+            PairForce::Yukawa { screening } => vec![*screening],
+            PairForce::Spring { rest_length, range } => vec![*rest_length, *range],
+        }
+    }
+}
+
+impl ForceLaw for PairForce {
+    fn force(&self, distance: f32, coefficient: f32, who: ParticleColor, to: ParticleColor, radii: PairRadii) -> f32 {
+        let force = match self {
+            PairForce::Real { softening } => return physics::real::calculate_force(coefficient, distance, *softening),
+            PairForce::Emergence => return physics::emergence::calculate_force(coefficient, distance, radii),
+            PairForce::Custom(law) => return law.force(distance, coefficient, who, to, radii),
+            PairForce::LennardJones { sigma } => {
+                if distance >= sigma * LENNARD_JONES_CUTOFF {
+                    return 0.;
+                }
+                let six = (sigma / distance).powi(6);
+                -24. * coefficient * (2. * six * six - six) / distance
+            },
+            PairForce::Morse { equilibrium, width } => {
+                let decay = f32::exp(-width * (distance - equilibrium));
+                2. * width * coefficient * (1. - decay) * decay
+            },
+            PairForce::Yukawa { screening } => {
+                coefficient * f32::exp(-distance / screening) * (1. + distance / screening) / distance.powi(2)
+            },
+            PairForce::Spring { rest_length, range } => {
+                if distance > *range {
+                    return 0.;
+                }
+                coefficient * (distance - rest_length)
+            },
+        };
+
+        if force.is_nan() {
+            return 0.;
+        }
+        calc::bounded_value(force, -MAX_FORCE, MAX_FORCE)
+    }
+}
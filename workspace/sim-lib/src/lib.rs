@@ -2,59 +2,226 @@ mod calc;
 mod def;
 mod physics;
 mod constants;
+#[cfg(feature = "gpu")]
 mod gpu;
+mod grid;
+mod obstacle;
+mod field;
+mod law;
+mod bond;
+mod reaction;
+mod thermostat;
+mod impulse;
+mod health;
+mod analysis;
+mod recording;
+mod scenario;
+#[cfg(feature = "threadpool")]
+mod search;
+#[cfg(feature = "threadpool")]
+mod sweep;
 
+use rand::{SeedableRng, rngs::StdRng};
+#[cfg(feature = "threadpool")]
 use std::sync::Arc;
+#[cfg(feature = "threadpool")]
 use crossbeam_channel::Sender;
-use femtovg::Canvas;
 
+#[cfg(feature = "threadpool")]
 pub use threadpool::ThreadPool;
-pub use def::{Particle, ParticleColor, Point, Vector, ForcesConfig};
-pub use physics::PhysicsMode;
-pub use calc::{random_world_position, bounded_value};
+pub use def::{Particle, ParticleColor, Point, Vector, ForcesConfig, PairRadii};
+pub use physics::{PhysicsMode, PhysicsParams, real::DEFAULT_SOFTENING};
+pub use obstacle::Obstacle;
+pub use field::{ExternalField, Field, VectorGrid};
+pub use law::{ForceLaw, PairForce};
+pub use bond::{AngleConstraint, Bond, BondRule};
+pub use reaction::Reaction;
+pub use thermostat::{Thermostat, DEFAULT_LANGEVIN_DAMPING};
+pub use impulse::Impulse;
+pub use health::{HealthIssue, HealthPolicy, TickStage};
+pub use constants::{WORLD_WIDTH_FLOAT, WORLD_HEIGHT_FLOAT, WORLD_UNIT_SIZE, FORCE_SCALAR};
+pub use calc::{random_world_position, random_world_position_with, bounded_value};
+#[cfg(feature = "gpu")]
 pub use gpu::Executor as GpuExecutor;
+pub use analysis::{RadialDistribution, PairStatistics, mean_kinetic_energy, mean_displacement, cluster_count, cluster_labels, local_density};
+pub use recording::{Recorder, Replay};
+pub use scenario::{Scenario, ScenarioError};
+#[cfg(feature = "threadpool")]
+pub use search::{Fitness, ClusterCount, Motility, KineticEnergy, SearchConfig, Candidate, GeneticSearch};
+#[cfg(feature = "threadpool")]
+pub use sweep::{Sweep, SweepAxis, SweepRun, SweepMetrics};
 
 pub struct World {
     particles: Vec<Particle>,
     forces: ForcesConfig,
     physics_mode: PhysicsMode,
+    law: PairForce,
+    params: PhysicsParams,
+    obstacles: Vec<Obstacle>,
+    fields: Vec<Field>,
+    bonds: bond::Bonds,
+    reactions: reaction::Reactions,
+    thermostat: Option<Thermostat>,
+    // None skips the checks altogether
+    health: Option<HealthPolicy>,
+    // Found during the last tick, the policy has dealt with those before `health_pending`
+    health_issues: Vec<HealthIssue>,
+    health_pending: usize,
+    // Everything random during a tick draws from here, so seeded worlds replay exactly
+    rng: StdRng,
+    ticks: u64,
+    // Fraction of a tick the last substep took, none before the first one
+    last_substep: Option<f32>,
+    recorder: Option<Recorder>,
+    // A failed write stops the recording, this waits for `stop_recording`
+    recording_error: Option<std::io::Error>,
 }
 
 pub enum CalculationMethod {
+    #[cfg(feature = "threadpool")]
     ThreadPool(ThreadPool),
+    #[cfg(feature = "gpu")]
     GPU(GpuExecutor),
     SingleThread
 }
 
+impl CalculationMethod {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "threadpool")]
+            CalculationMethod::ThreadPool(_) => "thread pool",
+            #[cfg(feature = "gpu")]
+            CalculationMethod::GPU(_) => "gpu",
+            CalculationMethod::SingleThread => "single thread",
+        }
+    }
+}
+
 impl World {
     pub fn new(particles: Vec<Particle>, forces: ForcesConfig, physics: PhysicsMode) -> Self {
         World {
             particles,
             forces,
             physics_mode: physics,
+            law: PairForce::for_mode(physics),
+            params: PhysicsParams::default(),
+            obstacles: Vec::new(),
+            fields: Vec::new(),
+            bonds: bond::Bonds::default(),
+            reactions: reaction::Reactions::default(),
+            thermostat: None,
+            health: None,
+            health_issues: Vec::new(),
+            health_pending: 0,
+            rng: StdRng::from_rng(&mut rand::rng()),
+            ticks: 0,
+            last_substep: None,
+            recorder: None,
+            recording_error: None,
         }
     }
 
-    pub fn tick(&mut self, calculation_method: &CalculationMethod) {
-        self.update_velocities(calculation_method);
-        self.update_positions();
+    pub fn with_physics_params(mut self, params: PhysicsParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    // Replaces the pair force the physics mode would use, friction and edges still follow the mode
+    pub fn with_force_law(mut self, law: PairForce) -> Self {
+        self.law = law;
+        self
     }
 
-    pub fn draw<R: femtovg::Renderer>(&self, canvas: &mut Canvas<R>, camera_position: Point, scale_factor: f32) {
-        let min_x = camera_position.x;
-        let max_x = camera_position.x + (canvas.width() as f32 / scale_factor);
-        let min_y = camera_position.y;
-        let max_y = camera_position.y + (canvas.height() as f32 / scale_factor);
+    pub fn with_obstacles(mut self, obstacles: Vec<Obstacle>) -> Self {
+        self.obstacles = obstacles;
+        self
+    }
 
-        for particle in self.particles.iter() {
-            if particle.position.x < min_x || particle.position.x > max_x || particle.position.y < min_y || particle.position.y > max_y {
-                continue;
-            }
+    pub fn with_fields(mut self, fields: Vec<Field>) -> Self {
+        self.fields = fields;
+        self
+    }
 
-            let mut path = femtovg::Path::new();
-            path.circle((particle.position.x - camera_position.x) * scale_factor, (particle.position.y - camera_position.y) * scale_factor, constants::BASE_PARTICLE_RADIUS * scale_factor);
-            canvas.fill_path(&path, &femtovg::Paint::color(particle.color.into()));
+    // Like `set_bonds`, bonds on particles that don't exist are dropped
+    pub fn with_bonds(mut self, bonds: Vec<Bond>) -> Self {
+        self.set_bonds(bonds);
+        self
+    }
+
+    pub fn with_angle_constraints(mut self, angles: Vec<AngleConstraint>) -> Self {
+        self.bonds.angles = angles;
+        self.bonds.truncate(self.particles.len());
+        self
+    }
+
+    pub fn with_bond_rules(mut self, rules: Vec<BondRule>) -> Self {
+        self.bonds.rules = rules;
+        self
+    }
+
+    // Rules turning a species into itself, and contacts without a positive distance or a probability between 0 and 1,
+    // are ignored
+    pub fn with_reactions(mut self, reactions: Vec<Reaction>) -> Self {
+        self.reactions = reaction::Reactions::new(reactions);
+        self
+    }
+
+    pub fn with_thermostat(mut self, thermostat: Option<Thermostat>) -> Self {
+        self.thermostat = thermostat;
+        self
+    }
+
+    pub fn with_health_check(mut self, policy: Option<HealthPolicy>) -> Self {
+        self.health = policy;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.reseed(seed);
+        self
+    }
+
+    // Moves everything `dt` ticks ahead, in as many substeps as it takes to keep it stable, and returns how many
+    // that was. Aging, bonds forming and breaking, and reactions still happen once per call. `dt` has to be finite
+    // and positive.
+    pub fn tick(&mut self, calculation_method: &CalculationMethod, dt: f32) -> u32 {
+        assert!(dt.is_finite() && dt > 0., "Tick length has to be finite and positive, got {}", dt);
+
+        // Bursts and close encounters can start halfway through a tick, so the step is picked again before every
+        // substep. Velocities live half a step off the positions, kicking over the mean of the steps on either
+        // side keeps them there when the step changes, instead of leaking energy at every change.
+        self.health_issues.clear();
+        self.health_pending = 0;
+        self.check_health(TickStage::BeforeTick);
+        self.enforce_health();
+
+        let (mut remaining, mut substeps) = (dt, 0);
+        while remaining > 0. {
+            let accelerations = self.pair_accelerations(calculation_method);
+            let step = self.substep_length(&accelerations, dt).min(remaining);
+            self.update_velocities(&accelerations, (self.last_substep.unwrap_or(step) + step) / 2.);
+            self.update_positions(step);
+            self.check_health(TickStage::Motion);
+            // Before the next substep, where bad positions would spread through the pair forces
+            self.enforce_health();
+            self.last_substep = Some(step);
+            remaining -= step;
+            substeps += 1;
+        }
+
+        self.particles.iter_mut().for_each(|particle| particle.age += 1);
+        if !self.bonds.is_empty() {
+            self.bonds.break_overstrained(&self.particles);
+            self.bonds.form(&self.particles);
         }
+        self.reactions.react(&mut self.particles, &mut self.rng);
+        self.ticks += 1;
+
+        if let Some(Err(e)) = self.recorder.as_mut().map(|recorder| recorder.record(self.ticks, self.physics_mode, &self.particles)) {
+            self.recorder = None;
+            self.recording_error = Some(e);
+        }
+        substeps
     }
 
     pub fn set_forces_config(&mut self, forces: ForcesConfig) {
@@ -65,53 +232,361 @@ impl World {
         self.forces
     }
 
+    pub fn set_force_law(&mut self, law: PairForce) {
+        self.law = law;
+    }
+
+    pub fn force_law(&self) -> &PairForce {
+        &self.law
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn set_health_check(&mut self, policy: Option<HealthPolicy>) {
+        self.health = policy;
+    }
+
+    pub fn health_check(&self) -> Option<HealthPolicy> {
+        self.health
+    }
+
+    // Particles found with a non-finite position or velocity during the last tick, empty without a health check
+    pub fn health_issues(&self) -> &[HealthIssue] {
+        &self.health_issues
+    }
+
+    pub fn set_thermostat(&mut self, thermostat: Option<Thermostat>) {
+        self.thermostat = thermostat;
+    }
+
+    pub fn thermostat(&self) -> Option<Thermostat> {
+        self.thermostat
+    }
+
+    // Ignored when it isn't valid, see `with_reactions`
+    pub fn add_reaction(&mut self, reaction: Reaction) {
+        self.reactions.add(reaction);
+    }
+
+    pub fn reactions(&self) -> &[Reaction] {
+        &self.reactions.rules
+    }
+
+    // How many particles each reaction changed, in the order of `reactions`
+    pub fn reaction_counts(&self) -> &[u64] {
+        &self.reactions.counts
+    }
+
+    pub fn set_physics_params(&mut self, params: PhysicsParams) {
+        self.params = params;
+    }
+
+    pub fn get_physics_params(&self) -> PhysicsParams {
+        self.params
+    }
+
+    // Bonds keep pointing at the same indices, those past the end of the new particles are dropped
+    pub fn set_particles(&mut self, particles: Vec<Particle>) {
+        self.particles = particles;
+        self.bonds.truncate(self.particles.len());
+    }
+
+    // Removes the particles flagged in `removed` along with their bonds, the other bonds follow their particles
+    pub fn remove_particles(&mut self, removed: &[bool]) {
+        let mut new_index = vec![None; self.particles.len()];
+        let mut kept = 0;
+        for (i, index) in new_index.iter_mut().enumerate() {
+            if !removed.get(i).copied().unwrap_or(false) {
+                *index = Some(kept);
+                kept += 1;
+            }
+        }
+
+        let mut i = 0;
+        self.particles.retain(|_| {
+            i += 1;
+            new_index[i - 1].is_some()
+        });
+        bond::remap(&mut self.bonds.bonds, &mut self.bonds.angles, &new_index);
+    }
+
+    // Indices out of range are ignored
+    pub fn add_bond(&mut self, bond: Bond) {
+        if bond.a != bond.b && bond.a < self.particles.len() && bond.b < self.particles.len() {
+            self.bonds.bonds.push(bond);
+        }
+    }
+
+    pub fn set_bonds(&mut self, bonds: Vec<Bond>) {
+        self.bonds.bonds = bonds;
+        self.bonds.truncate(self.particles.len());
+    }
+
+    pub fn bonds(&self) -> &[Bond] {
+        &self.bonds.bonds
+    }
+
+    pub fn add_angle_constraint(&mut self, angle: AngleConstraint) {
+        let len = self.particles.len();
+        if angle.a < len && angle.center < len && angle.b < len {
+            self.bonds.angles.push(angle);
+        }
+    }
+
+    pub fn angle_constraints(&self) -> &[AngleConstraint] {
+        &self.bonds.angles
+    }
+
+    pub fn add_bond_rule(&mut self, rule: BondRule) {
+        self.bonds.rules.push(rule);
+    }
+
+    pub fn bond_rules(&self) -> &[BondRule] {
+        &self.bonds.rules
+    }
+
+    pub fn set_obstacles(&mut self, obstacles: Vec<Obstacle>) {
+        self.obstacles = obstacles;
+    }
+
+    pub fn obstacles(&self) -> &[Obstacle] {
+        &self.obstacles
+    }
+
+    pub fn add_field(&mut self, field: Field) {
+        self.fields.push(field);
+    }
+
+    pub fn set_fields(&mut self, fields: Vec<Field>) {
+        self.fields = fields;
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub fn particles_mut(&mut self) -> &mut [Particle] {
+        &mut self.particles
+    }
+
+    pub fn physics_mode(&self) -> PhysicsMode {
+        self.physics_mode
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn start_recording(&mut self, mut recorder: Recorder) -> std::io::Result<()> {
+        recorder.record(self.ticks, self.physics_mode, &self.particles)?;
+        self.recorder = Some(recorder);
+        self.recording_error = None;
+        Ok(())
+    }
+
+    // Fails with the error that stopped the recording early, if a frame could not be written
+    pub fn stop_recording(&mut self) -> std::io::Result<Option<Recorder>> {
+        match self.recording_error.take() {
+            Some(e) => Err(e),
+            None => Ok(self.recorder.take()),
+        }
+    }
+
     pub fn accelerate_all(&mut self, amount: f32) {
-        self.particles.iter_mut().for_each(|p| p.velocity = p.velocity.with_length(p.velocity.length() + f32::abs(amount)));
+        self.apply_impulse(Impulse::Boost { amount: f32::abs(amount) });
+    }
+
+    // Random directions come from the world's generator, so seeded worlds get the same kicks every run
+    pub fn apply_impulse(&mut self, impulse: Impulse) {
+        impulse.apply(&mut self.particles, None, &mut self.rng);
+    }
+
+    pub fn apply_impulse_to(&mut self, impulse: Impulse, color: ParticleColor) {
+        impulse.apply(&mut self.particles, Some(color), &mut self.rng);
     }
 
-    fn update_velocities(&mut self, calculation_method: &CalculationMethod) {
-        if self.physics_mode == PhysicsMode::Emergence {
-            self.particles.iter_mut().for_each(physics::emergence::apply_friction);
+    // What each species adds to the particle's velocity in one tick, before friction
+    pub fn acceleration_contributions(&self, index: usize) -> [Vector; ParticleColor::matrix_len()] {
+        let mut contributions = [Vector::zero(); ParticleColor::matrix_len()];
+        let target = &self.particles[index];
+
+        for (j, other) in self.particles.iter().enumerate() {
+            if j == index {
+                continue;
+            }
+            if let Some(acc) = calc::acceleration_of(target, other, &self.forces, &self.law, &self.params) {
+                contributions[other.color as usize] += acc;
+            }
         }
+        contributions
+    }
 
+    fn pair_accelerations(&self, calculation_method: &CalculationMethod) -> Vec<Vector> {
         match calculation_method {
-            CalculationMethod::ThreadPool(pool) => self.thread_pool_update_velocities(pool),
-            CalculationMethod::GPU(executor) => self.gpu_update_velocities(executor),
-            CalculationMethod::SingleThread => self.no_thread_pool_update_velocities(),
+            #[cfg(feature = "threadpool")]
+            CalculationMethod::ThreadPool(pool) => self.thread_pool_pair_accelerations(pool),
+            #[cfg(feature = "gpu")]
+            CalculationMethod::GPU(executor) => self.gpu_pair_accelerations(executor),
+            CalculationMethod::SingleThread => self.no_thread_pool_pair_accelerations(),
+        }
+    }
+
+    // Short enough that nothing moves further than the max displacement, and that close encounters under the real
+    // law are followed through, but no shorter than the max substeps allow. Only the pair forces are looked at,
+    // fields and bonds are gentle next to them.
+    fn substep_length(&self, accelerations: &[Vector], dt: f32) -> f32 {
+        let max_distance = self.params.max_displacement * self.params.repel_radius * WORLD_UNIT_SIZE;
+        let mut step = self.particles.iter()
+            .zip(accelerations)
+            .filter(|(particle, _)| !particle.pinned)
+            .map(|(particle, acc)| physics::stable_step(particle.velocity, *acc, max_distance))
+            .fold(dt, f32::min);
+
+        // Only the real law has a singular core, everything else is bounded
+        if let PairForce::Real { softening } = self.law {
+            let max_acceleration = accelerations.iter().map(|a| a.length()).fold(0., f32::max);
+            step = step.min(physics::real::encounter_step(max_acceleration, softening));
+        }
+        step.max(dt / self.params.max_substeps as f32)
+    }
+
+    fn update_velocities(&mut self, accelerations: &[Vector], dt: f32) {
+        // A thermostat does its own damping, friction on top would keep the world below its target
+        if self.physics_mode == PhysicsMode::Emergence && self.thermostat.is_none() {
+            let friction = self.params.friction.powf(dt);
+            self.particles.iter_mut().for_each(|p| physics::emergence::apply_friction(p, friction));
+        }
+
+        self.particles.iter_mut().zip(accelerations).for_each(|(particle, acc)| particle.velocity += *acc * dt);
+        self.check_health(TickStage::PairForces);
+
+        // Cheap next to the pairwise forces, so every calculation method shares these
+        for particle in self.particles.iter_mut() {
+            particle.velocity += self.fields.iter().fold(Vector::zero(), |sum, field| sum + field.acceleration(particle)) * dt;
+        }
+        self.check_health(TickStage::Fields);
+        self.bonds.apply(&mut self.particles, dt);
+        self.check_health(TickStage::Bonds);
+
+        // Last, so the thermostat sees everything else this tick did to the velocities
+        if let Some(thermostat) = self.thermostat {
+            thermostat.apply(&mut self.particles, dt, &mut self.rng);
+            self.check_health(TickStage::Thermostat);
+        }
+    }
+
+    // Records the particles that went bad at `stage`, those already waiting on the policy went bad earlier
+    fn check_health(&mut self, stage: TickStage) {
+        if self.health.is_none() {
+            return;
+        }
+
+        for (i, particle) in self.particles.iter().enumerate() {
+            if health::is_healthy(particle) || self.health_issues[self.health_pending..].iter().any(|issue| issue.particle == i) {
+                continue;
+            }
+            self.health_issues.push(HealthIssue {
+                particle: i,
+                tick: self.ticks + 1,
+                stage,
+                position: particle.position,
+                velocity: particle.velocity,
+                removed: false,
+            });
+        }
+    }
+
+    fn enforce_health(&mut self) {
+        let (Some(policy), pending) = (self.health, self.health_pending) else {
+            return;
+        };
+        if pending == self.health_issues.len() {
+            return;
+        }
+        self.health_pending = self.health_issues.len();
+
+        let issues = &self.health_issues[pending..];
+        match policy {
+            HealthPolicy::Panic => {
+                let report: Vec<String> = issues.iter().map(|issue| issue.to_string()).collect();
+                panic!("{}", report.join("\n"));
+            },
+            HealthPolicy::Repair => {
+                for issue in issues {
+                    let particle = &mut self.particles[issue.particle];
+                    if !(particle.position.x.is_finite() && particle.position.y.is_finite()) {
+                        particle.position = random_world_position_with(&mut self.rng);
+                    }
+                    particle.velocity = Vector::zero();
+                }
+            },
+            HealthPolicy::Remove => {
+                let mut removed = vec![false; self.particles.len()];
+                issues.iter().for_each(|issue| removed[issue.particle] = true);
+                self.remove_particles(&removed);
+                self.health_issues[pending..].iter_mut().for_each(|issue| issue.removed = true);
+            },
         }
     }
 
-    fn update_positions(&mut self) {
+    fn update_positions(&mut self, dt: f32) {
+        let rng = &mut self.rng;
         self.particles.iter_mut().for_each(|particle| {
-            particle.position += particle.velocity;
-            match self.physics_mode {
-                PhysicsMode::Real => physics::real::out_of_bounds_fixup(particle),
-                PhysicsMode::Emergence  => physics::emergence::out_of_bounds_fixup(particle),
+            // Whatever the forces did to a pinned particle this tick is thrown away
+            if particle.pinned {
+                particle.velocity = Vector::zero();
+                return;
             }
+
+            let previous = particle.position;
+            particle.position += particle.velocity * dt;
+            // Edges first, so walls reaching the edge of the world can't be slipped past by going around their end.
+            // Particles Emergence moves to a random spot never traveled there, only where they land is checked.
+            let moved_from = match self.physics_mode {
+                PhysicsMode::Real => {
+                    physics::real::out_of_bounds_fixup(particle);
+                    previous
+                },
+                PhysicsMode::Emergence => {
+                    let teleported = calc::is_out_of_bounds(&particle.position);
+                    physics::emergence::out_of_bounds_fixup(particle, rng);
+                    if teleported { particle.position } else { previous }
+                },
+            };
+            self.obstacles.iter().for_each(|obstacle| obstacle.collide(moved_from, particle));
         })
     }
 
-    fn gpu_update_velocities(&mut self, executor: &GpuExecutor) {
+    #[cfg(feature = "gpu")]
+    fn gpu_pair_accelerations(&self, executor: &GpuExecutor) -> Vec<Vector> {
         assert_eq!(self.physics_mode, PhysicsMode::Emergence);
+        // The kernel only knows the emergence law
+        if !matches!(self.law, PairForce::Emergence) {
+            return self.no_thread_pool_pair_accelerations();
+        }
 
-        executor.calculate_emergence_accelerations(self.particles.as_slice(), &self.forces)
-            .into_iter()
-            .enumerate()
-            .for_each(|(i, acc)| {
-                self.particles[i].velocity += acc;
-            });
+        executor.calculate_emergence_accelerations(self.particles.as_slice(), &self.forces, &self.params)
     }
 
-    fn no_thread_pool_update_velocities(&mut self) {
+    fn no_thread_pool_pair_accelerations(&self) -> Vec<Vector> {
+        let mut accelerations = vec![Vector::zero(); self.particles.len()];
         for i in 0..self.particles.len() {
             for j in i + 1..self.particles.len() {
-                self.update_velocity_for(i, j);
-                self.update_velocity_for(j, i);
+                accelerations[i] += self.acceleration_between(i, j);
+                accelerations[j] += self.acceleration_between(j, i);
             }
         }
+        accelerations
     }
 
-    fn thread_pool_update_velocities(&mut self, thread_pool: &ThreadPool) {
+    #[cfg(feature = "threadpool")]
+    fn thread_pool_pair_accelerations(&self, thread_pool: &ThreadPool) -> Vec<Vector> {
         let total_jobs = thread_pool.max_count();
         let (sender, receiver) = crossbeam_channel::bounded(total_jobs);
         let copied_particles = Arc::new(self.particles.clone());
@@ -127,28 +602,33 @@ impl World {
                 chunk_size,
                 thread_pool,
                 sender.clone(),
-                self.forces,
-                self.physics_mode
+                JobSettings {
+                    forces: self.forces,
+                    law: self.law.clone(),
+                    params: self.params,
+                }
             )
         });
 
+        let mut accelerations = vec![Vector::zero(); self.particles.len()];
         receiver.iter().take(total_jobs).for_each(|job_result| {
             job_result.accelerations.into_iter().enumerate().for_each(|(i, a)| {
                 if let Some(a) = a {
-                    self.particles[job_result.chunk_start_index + i].velocity += a;
+                    accelerations[job_result.chunk_start_index + i] += a;
                 }
             })
         });
+        accelerations
     }
 
+    #[cfg(feature = "threadpool")]
     fn start_accelerations_calculation_job(
         particles: Arc<Vec<Particle>>,
         chunk_start_index: usize,
         chunk_size: usize,
         thread_pool: &ThreadPool,
         result_sender: Sender<JobResult>,
-        forces: ForcesConfig,
-        physics_mode: PhysicsMode)
+        settings: JobSettings)
     {
         thread_pool.execute(move || {
             let mut result = JobResult {
@@ -158,7 +638,7 @@ impl World {
 
             for i in 0..chunk_size {
                 for j in 0..particles.len() {
-                    let acc = calc::acceleration_of(&particles[chunk_start_index+i], &particles[j], &forces, physics_mode);
+                    let acc = calc::acceleration_of(&particles[chunk_start_index+i], &particles[j], &settings.forces, &settings.law, &settings.params);
                     result.accelerations[i] = result.accelerations[i].map_or(acc, |x| Some(acc.map_or(x, |y| x + y)));
                 }
             }
@@ -167,18 +647,26 @@ impl World {
         });
     }
 
-    fn update_velocity_for(&mut self, target_particle_index: usize, other_particle_index: usize) {
-        if let Some(acc) = calc::acceleration_of(
+    fn acceleration_between(&self, target_particle_index: usize, other_particle_index: usize) -> Vector {
+        calc::acceleration_of(
             &self.particles[target_particle_index],
             &self.particles[other_particle_index],
             &self.forces,
-            self.physics_mode
-        ) {
-            self.particles[target_particle_index].velocity += acc;
-        }
+            &self.law,
+            &self.params
+        ).unwrap_or_default()
     }
 }
 
+#[cfg(feature = "threadpool")]
+#[derive(Clone)]
+struct JobSettings {
+    forces: ForcesConfig,
+    law: PairForce,
+    params: PhysicsParams,
+}
+
+#[cfg(feature = "threadpool")]
 struct JobResult {
     chunk_start_index: usize,
     accelerations: Vec<Option<Vector>>,
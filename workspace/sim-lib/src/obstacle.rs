@@ -0,0 +1,149 @@
+use crate::{Particle, Point, Vector};
+
+// How far particles are kept off obstacle surfaces, so they don't end up exactly on an edge
+const SURFACE_OFFSET: f32 = 0.01;
+
+// Static shapes particles bounce off elastically, in world coordinates.
+// Walls are one segment thick, circles and polygons are solid.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Obstacle {
+    Wall { from: Point, to: Point },
+    Circle { center: Point, radius: f32 },
+    Polygon { points: Vec<Point> },
+}
+
+impl Obstacle {
+    // Segments making up the outline, circles have none
+    pub fn edges(&self) -> Vec<(Point, Point)> {
+        match self {
+            Obstacle::Wall { from, to } => vec![(*from, *to)],
+            Obstacle::Circle { .. } => Vec::new(),
+            Obstacle::Polygon { points } => (0..points.len()).map(|i| (points[i], points[(i + 1) % points.len()])).collect(),
+        }
+    }
+
+    // `previous` is where the particle was before this tick's move
+    pub(crate) fn collide(&self, previous: Point, particle: &mut Particle) {
+        match self {
+            Obstacle::Wall { from, to } => {
+                if let Some((_, normal)) = crossing(previous, particle.position, *from, *to) {
+                    bounce(particle, *from, normal);
+                }
+            },
+            Obstacle::Circle { center, radius } => collide_circle(*center, *radius, previous, particle),
+            Obstacle::Polygon { points } => collide_polygon(points, previous, particle),
+        }
+    }
+}
+
+fn collide_circle(center: Point, radius: f32, previous: Point, particle: &mut Particle) {
+    let offset = particle.position - center;
+    let distance = offset.length();
+    if distance >= radius {
+        return;
+    }
+
+    let normal = if distance > 0. { offset / distance } else { Vector::new(1., 0.) };
+    if (previous - center).length() >= radius {
+        // Mirrored through the tangent at the point of entry
+        particle.position = center + normal * (2. * radius - distance + SURFACE_OFFSET);
+        reflect_velocity(particle, normal);
+    } else {
+        // Already inside before moving (spawned or teleported there), just push it out
+        particle.position = center + normal * (radius + SURFACE_OFFSET);
+        remove_inward_velocity(particle, normal);
+    }
+}
+
+fn collide_polygon(points: &[Point], previous: Point, particle: &mut Particle) {
+    if points.len() < 3 {
+        return;
+    }
+
+    let first_crossing = (0..points.len())
+        .filter_map(|i| {
+            let (from, to) = (points[i], points[(i + 1) % points.len()]);
+            crossing(previous, particle.position, from, to).map(|(t, normal)| (t, from, normal))
+        })
+        .min_by(|(a, _, _), (b, _, _)| a.total_cmp(b));
+
+    if let Some((_, from, normal)) = first_crossing {
+        bounce(particle, from, normal);
+    }
+
+    if !contains(points, particle.position) {
+        return;
+    }
+
+    // Still inside, either from before this tick or after bouncing into a concave corner
+    let closest = (0..points.len())
+        .map(|i| closest_point_on_segment(particle.position, points[i], points[(i + 1) % points.len()]))
+        .min_by(|a, b| (*a - particle.position).square_length().total_cmp(&(*b - particle.position).square_length()));
+    if let Some(closest) = closest {
+        let outward = (closest - particle.position).try_normalize().unwrap_or(Vector::new(1., 0.));
+        particle.position = closest + outward * SURFACE_OFFSET;
+        remove_inward_velocity(particle, outward);
+    }
+}
+
+// Where the move from `start` to `end` crosses the segment, as the fraction of the move and the segment
+// normal facing the side the move started on
+fn crossing(start: Point, end: Point, from: Point, to: Point) -> Option<(f32, Vector)> {
+    let (movement, segment) = (end - start, to - from);
+    let denominator = movement.cross(segment);
+    if denominator == 0. {
+        return None;
+    }
+
+    let t = (from - start).cross(segment) / denominator;
+    let u = (from - start).cross(movement) / denominator;
+    if !(0. ..=1.).contains(&t) || !(0. ..=1.).contains(&u) {
+        return None;
+    }
+
+    let normal = Vector::new(-segment.y, segment.x).try_normalize()?;
+    let normal = if normal.dot(start - from) >= 0. { normal } else { -normal };
+    Some((t, normal))
+}
+
+// Mirrors the particle back through the line with the given normal, which faces the side it should end up on
+fn bounce(particle: &mut Particle, on_line: Point, normal: Vector) {
+    let depth = (particle.position - on_line).dot(normal);
+    particle.position -= normal * (2. * depth - SURFACE_OFFSET);
+    reflect_velocity(particle, normal);
+}
+
+fn reflect_velocity(particle: &mut Particle, normal: Vector) {
+    let along_normal = particle.velocity.dot(normal);
+    if along_normal < 0. {
+        particle.velocity -= normal * 2. * along_normal;
+    }
+}
+
+fn remove_inward_velocity(particle: &mut Particle, outward: Vector) {
+    let along_normal = particle.velocity.dot(outward);
+    if along_normal < 0. {
+        particle.velocity -= outward * along_normal;
+    }
+}
+
+// Even-odd rule
+fn contains(points: &[Point], position: Point) -> bool {
+    let mut inside = false;
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[(i + 1) % points.len()]);
+        if (a.y > position.y) != (b.y > position.y) && position.x < a.x + (position.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn closest_point_on_segment(position: Point, from: Point, to: Point) -> Point {
+    let segment = to - from;
+    let length = segment.square_length();
+    if length == 0. {
+        return from;
+    }
+    from + segment * ((position - from).dot(segment) / length).clamp(0., 1.)
+}
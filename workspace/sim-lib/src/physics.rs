@@ -1,27 +1,106 @@
+use rand::Rng;
 use crate::{
-    calc, Particle, random_world_position,
-    constants::{WORLD_HEIGHT_FLOAT, WORLD_WIDTH_FLOAT},
+    calc, PairRadii, Particle, Vector, random_world_position_with,
+    constants::{WORLD_HEIGHT_FLOAT, WORLD_UNIT_SIZE, WORLD_WIDTH_FLOAT},
     def::WorldEdge,
 };
 
+pub const MAX_SUBSTEPS: u32 = 16;
+pub const MAX_DISPLACEMENT: f32 = 0.5;
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum PhysicsMode {
     Real,
     Emergence,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PhysicsParams {
+    // Emergence only, and ignored while the world has a thermostat
+    pub friction: f32,
+    pub repel_radius: f32,
+    // Upper bound on how many substeps a tick is split into, 1 never splits
+    pub max_substeps: u32,
+    // How far a particle may move in one substep, in repel radii
+    pub max_displacement: f32,
+}
+
+impl Default for PhysicsParams {
+    fn default() -> Self {
+        PhysicsParams {
+            friction: emergence::FRICTION_MULTIPLIER,
+            repel_radius: emergence::GLOBAL_REPEL_FORCE_RADIUS,
+            max_substeps: MAX_SUBSTEPS,
+            max_displacement: MAX_DISPLACEMENT,
+        }
+    }
+}
+
+impl PhysicsParams {
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn with_repel_radius(mut self, repel_radius: f32) -> Self {
+        self.repel_radius = repel_radius;
+        self
+    }
+
+    pub fn with_max_substeps(mut self, max_substeps: u32) -> Self {
+        self.max_substeps = max_substeps.max(1);
+        self
+    }
+
+    pub fn with_max_displacement(mut self, max_displacement: f32) -> Self {
+        self.max_displacement = max_displacement;
+        self
+    }
+}
+
+// Longest step, in ticks, over which a particle starting at `velocity` and speeding up at `acceleration` covers no
+// more than `max_distance` pixels. Infinite for a particle at rest.
+pub fn stable_step(velocity: Vector, acceleration: Vector, max_distance: f32) -> f32 {
+    let (speed, acceleration) = (velocity.length(), acceleration.length());
+    2. * max_distance / (speed + f32::sqrt(speed * speed + 4. * acceleration * max_distance))
+}
+
+impl PhysicsMode {
+    pub const fn name(self) -> &'static str {
+        match self {
+            PhysicsMode::Real => "real",
+            PhysicsMode::Emergence => "emergence",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [PhysicsMode::Real, PhysicsMode::Emergence].into_iter().find(|m| m.name().eq_ignore_ascii_case(name))
+    }
+}
+
 pub mod real {
     use super::*;
 
-    pub const MAX_APPLIED_FORCE: f32 = 0.1;
+    // In world units, a quarter of the distance the emergence pull reaches
+    pub const DEFAULT_SOFTENING: f32 = 0.25;
+    // Substeps last this fraction of the time it takes the hardest pulled particle to fall across the softening
+    // length, smaller is more accurate
+    pub const SUBSTEP_ACCURACY: f32 = 0.05;
 
-    pub fn calculate_force(configured_force: f32, distance: f32) -> f32 {
+    // Plummer softened inverse square, the true one past a few softening lengths, peaking and then going back
+    // to zero inside of it instead of growing without bound
+    pub fn calculate_force(configured_force: f32, distance: f32, softening: f32) -> f32 {
         if distance == 0. {
             return 0.;
         }
 
-        let force = configured_force / distance.powi(2);
-        calc::bounded_value(force, -MAX_APPLIED_FORCE, MAX_APPLIED_FORCE)
+        configured_force * distance / (distance.powi(2) + softening.powi(2)).powf(1.5)
+    }
+
+    // Longest substep, in ticks, that keeps the hardest pulled particle from cutting corners off its orbit.
+    // The acceleration is in pixels per tick squared and the softening in world units.
+    pub fn encounter_step(max_acceleration: f32, softening: f32) -> f32 {
+        SUBSTEP_ACCURACY * f32::sqrt(softening * WORLD_UNIT_SIZE / max_acceleration)
     }
 
     pub fn out_of_bounds_fixup(particle: &mut Particle) {
@@ -55,28 +134,29 @@ pub mod emergence {
 
     pub const FRICTION_MULTIPLIER: f32 = 0.65;
     pub const GLOBAL_REPEL_FORCE_RADIUS: f32 = 0.35;
+    pub const INTERACTION_RADIUS: f32 = 1.;
 
-    pub fn out_of_bounds_fixup(particle: &mut Particle) {
+    pub fn out_of_bounds_fixup<R: Rng + ?Sized>(particle: &mut Particle, rng: &mut R) {
         if !calc::is_out_of_bounds(&particle.position) {
             return;
         }
 
-        particle.position = random_world_position();
+        particle.position = random_world_position_with(rng);
     }
 
-    pub fn calculate_force(configured_force: f32, distance: f32) -> f32 {
-        if distance <= GLOBAL_REPEL_FORCE_RADIUS {
-            (distance / GLOBAL_REPEL_FORCE_RADIUS) - 1.
-        } else if distance < 1. {
-            let numerator = f32::abs((2. * distance) - 1. - GLOBAL_REPEL_FORCE_RADIUS);
-            let denominator = 1. - GLOBAL_REPEL_FORCE_RADIUS;
+    pub fn calculate_force(configured_force: f32, distance: f32, radii: PairRadii) -> f32 {
+        if distance <= radii.repel {
+            (distance / radii.repel) - 1.
+        } else if distance < radii.interaction {
+            let numerator = f32::abs((2. * distance) - radii.interaction - radii.repel);
+            let denominator = radii.interaction - radii.repel;
             configured_force * (1. - (numerator / denominator))
         } else {
             0.
         }
     }
 
-    pub fn apply_friction(particle: &mut Particle) {
-        particle.velocity *= FRICTION_MULTIPLIER;
+    pub fn apply_friction(particle: &mut Particle, friction: f32) {
+        particle.velocity *= friction;
     }
 }
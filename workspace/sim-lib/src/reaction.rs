@@ -0,0 +1,98 @@
+use rand::Rng;
+use crate::{
+    Particle, ParticleColor, calc,
+    constants::WORLD_UNIT_SIZE,
+    grid::SpatialGrid,
+};
+
+// Species changes evaluated once per tick, after particles moved. Every particle changes at most once per tick,
+// through the first rule that fires for it, and rules only see the species particles had before the tick's changes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Reaction {
+    // A `who` particle within `distance` world units of a `catalyst` one turns into `product` with `probability`
+    // per tick, the catalyst is left as it is
+    Contact { who: ParticleColor, catalyst: ParticleColor, distance: f32, product: ParticleColor, probability: f32 },
+    // A `who` particle turns into `product` once it has been a `who` for `ticks` ticks
+    Decay { who: ParticleColor, product: ParticleColor, ticks: u64 },
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Reactions {
+    pub(crate) rules: Vec<Reaction>,
+    // How many particles each rule changed so far
+    pub(crate) counts: Vec<u64>,
+}
+
+impl Reaction {
+    // A product different from the species it starts from, and for contacts a positive distance and a probability
+    // between 0 and 1
+    pub(crate) fn is_valid(&self) -> bool {
+        match *self {
+            Reaction::Contact { who, distance, product, probability, .. } => {
+                who != product && distance > 0. && (0. ..=1.).contains(&probability)
+            },
+            Reaction::Decay { who, product, .. } => who != product,
+        }
+    }
+}
+
+impl Reactions {
+    // Rules that aren't valid are left out
+    pub(crate) fn new(rules: Vec<Reaction>) -> Self {
+        let rules: Vec<Reaction> = rules.into_iter().filter(Reaction::is_valid).collect();
+        let counts = vec![0; rules.len()];
+        Reactions { rules, counts }
+    }
+
+    pub(crate) fn add(&mut self, rule: Reaction) {
+        if rule.is_valid() {
+            self.rules.push(rule);
+            self.counts.push(0);
+        }
+    }
+
+    pub(crate) fn react<R: Rng + ?Sized>(&mut self, particles: &mut [Particle], rng: &mut R) {
+        if self.rules.is_empty() {
+            return;
+        }
+
+        let colors: Vec<ParticleColor> = particles.iter().map(|p| p.color).collect();
+        let max_distance = self.rules.iter()
+            .filter_map(|rule| match rule {
+                Reaction::Contact { distance, .. } => Some(*distance),
+                Reaction::Decay { .. } => None,
+            })
+            .reduce(f32::max);
+        let grid = max_distance.map(|d| SpatialGrid::new(particles.iter().map(|p| &p.position), d * WORLD_UNIT_SIZE));
+
+        for i in 0..particles.len() {
+            for (rule_index, rule) in self.rules.iter().enumerate() {
+                let product = match *rule {
+                    Reaction::Contact { who, catalyst, distance, product, probability } => {
+                        let Some(grid) = grid.as_ref().filter(|_| colors[i] == who) else {
+                            continue;
+                        };
+                        let position = particles[i].position;
+                        let touching = grid.candidates_near(&position, distance * WORLD_UNIT_SIZE)
+                            .any(|j| j != i && colors[j] == catalyst && calc::world_distance(&position, &particles[j].position) < distance);
+                        // Rolled only on contact so the random sequence doesn't depend on unrelated particles
+                        if !touching || !rng.random_bool(probability as f64) {
+                            continue;
+                        }
+                        product
+                    },
+                    Reaction::Decay { who, product, ticks } => {
+                        if colors[i] != who || particles[i].age < ticks {
+                            continue;
+                        }
+                        product
+                    },
+                };
+
+                particles[i] = particles[i].with_color(product);
+                self.counts[rule_index] += 1;
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,347 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    path::Path,
+};
+use crate::{
+    Particle, ParticleColor, PhysicsMode, Point, Vector,
+    constants::{WORLD_HEIGHT_FLOAT, WORLD_WIDTH_FLOAT},
+};
+
+// Followed by the physics mode, written along with the first frame, then the frames
+const MAGIC: &[u8; 8] = b"PSIMREC2";
+const KEYFRAME_INTERVAL: u32 = 60;
+const POSITION_QUANTUM_STEPS: f32 = u16::MAX as f32;
+const VELOCITY_QUANTUM_PER_UNIT: f32 = 256.;
+
+const FRAME_KIND_KEY: u8 = 0;
+const FRAME_KIND_DELTA: u8 = 1;
+const FRAME_HEADER_LEN: usize = 1 + 8 + 4 + 4;
+
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+struct QuantizedParticle {
+    color: u8,
+    pinned: bool,
+    x: u16,
+    y: u16,
+    vx: i16,
+    vy: i16,
+}
+
+pub struct Recorder {
+    writer: Box<dyn Write + Send + Sync>,
+    interval: u32,
+    last_recorded_tick: Option<u64>,
+    frames_since_keyframe: u32,
+    previous: Vec<QuantizedParticle>,
+    payload: Vec<u8>,
+}
+
+impl Recorder {
+    pub fn create<P: AsRef<Path>>(path: P, interval: u32) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), interval)
+    }
+
+    pub fn new<W: Write + Send + Sync + 'static>(mut writer: W, interval: u32) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        Ok(Recorder {
+            writer: Box::new(writer),
+            interval: u32::max(interval, 1),
+            last_recorded_tick: None,
+            frames_since_keyframe: 0,
+            previous: Vec::new(),
+            payload: Vec::new(),
+        })
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub(crate) fn record(&mut self, tick: u64, physics_mode: PhysicsMode, particles: &[Particle]) -> io::Result<()> {
+        match self.last_recorded_tick {
+            Some(last) if tick < last + self.interval as u64 => return Ok(()),
+            Some(_) => {},
+            None => self.writer.write_all(&[physics_mode as u8])?,
+        }
+
+        let current: Vec<QuantizedParticle> = particles.iter().map(QuantizedParticle::from).collect();
+        let is_keyframe = self.previous.len() != current.len() || self.frames_since_keyframe == 0;
+
+        self.payload.clear();
+        if is_keyframe {
+            current.iter().for_each(|q| q.write_raw(&mut self.payload));
+        } else {
+            current.iter().zip(self.previous.iter()).for_each(|(q, prev)| q.write_delta(prev, &mut self.payload));
+        }
+
+        self.writer.write_all(&[if is_keyframe { FRAME_KIND_KEY } else { FRAME_KIND_DELTA }])?;
+        self.writer.write_all(&tick.to_le_bytes())?;
+        self.writer.write_all(&(current.len() as u32).to_le_bytes())?;
+        self.writer.write_all(&(self.payload.len() as u32).to_le_bytes())?;
+        self.writer.write_all(&self.payload)?;
+
+        self.frames_since_keyframe = (self.frames_since_keyframe + 1) % KEYFRAME_INTERVAL;
+        self.previous = current;
+        self.last_recorded_tick = Some(tick);
+        Ok(())
+    }
+}
+
+struct FrameIndex {
+    offset: usize,
+    payload_len: usize,
+    tick: u64,
+    count: usize,
+    is_keyframe: bool,
+}
+
+pub struct Replay {
+    data: Vec<u8>,
+    physics_mode: PhysicsMode,
+    frames: Vec<FrameIndex>,
+    position: usize,
+    state: Vec<QuantizedParticle>,
+}
+
+impl Replay {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut data = Vec::new();
+        File::open(path)?.read_to_end(&mut data)?;
+        Self::from_bytes(data)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> io::Result<Self> {
+        if data.len() < MAGIC.len() || &data[..MAGIC.len()] != MAGIC {
+            return Err(invalid_data("Not a simulation recording"));
+        }
+        let physics_mode = match data.get(MAGIC.len()) {
+            None => return Err(invalid_data("Recording has no frames")),
+            Some(&mode) if mode == PhysicsMode::Real as u8 => PhysicsMode::Real,
+            Some(&mode) if mode == PhysicsMode::Emergence as u8 => PhysicsMode::Emergence,
+            Some(_) => return Err(invalid_data("Unknown physics mode")),
+        };
+
+        let mut frames = Vec::new();
+        let mut offset = MAGIC.len() + 1;
+
+        // A frame cut short by an interrupted recording is dropped rather than treated as an error
+        while offset + FRAME_HEADER_LEN <= data.len() {
+            let kind = data[offset];
+            let tick = u64::from_le_bytes(data[offset + 1..offset + 9].try_into().unwrap());
+            let count = u32::from_le_bytes(data[offset + 9..offset + 13].try_into().unwrap()) as usize;
+            let payload_len = u32::from_le_bytes(data[offset + 13..offset + 17].try_into().unwrap()) as usize;
+
+            if offset + FRAME_HEADER_LEN + payload_len > data.len() {
+                break;
+            }
+
+            if kind != FRAME_KIND_KEY && kind != FRAME_KIND_DELTA {
+                return Err(invalid_data("Unknown frame kind"));
+            }
+
+            frames.push(FrameIndex { offset, payload_len, tick, count, is_keyframe: kind == FRAME_KIND_KEY });
+            offset += FRAME_HEADER_LEN + payload_len;
+        }
+
+        if frames.first().is_none_or(|f| !f.is_keyframe) {
+            return Err(invalid_data("Recording has no frames"));
+        }
+
+        let mut replay = Replay {
+            data,
+            physics_mode,
+            frames,
+            position: 0,
+            state: Vec::new(),
+        };
+        replay.decode_frame(0)?;
+        Ok(replay)
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn physics_mode(&self) -> PhysicsMode {
+        self.physics_mode
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.frames[self.position].tick
+    }
+
+    pub fn particles(&self) -> Vec<Particle> {
+        self.state.iter().map(Particle::from).collect()
+    }
+
+    // Stays on the current frame when one of the frames on the way can't be read
+    pub fn seek(&mut self, frame: usize) -> io::Result<()> {
+        let target = usize::min(frame, self.frames.len() - 1);
+
+        let keyframe = self.keyframe_before(target);
+        let start = if target >= self.position && keyframe <= self.position {
+            self.position + 1
+        } else {
+            keyframe
+        };
+
+        if let Err(e) = (start..=target).try_for_each(|i| self.decode_frame(i)) {
+            // Everything up to the current frame decoded fine before
+            (self.keyframe_before(self.position)..=self.position).try_for_each(|i| self.decode_frame(i))?;
+            return Err(e);
+        }
+
+        self.position = target;
+        Ok(())
+    }
+
+    pub fn step(&mut self, frames: isize) -> io::Result<()> {
+        self.seek(self.position.saturating_add_signed(frames))
+    }
+
+    fn keyframe_before(&self, index: usize) -> usize {
+        (0..=index).rev().find(|&i| self.frames[i].is_keyframe).unwrap_or(0)
+    }
+
+    fn decode_frame(&mut self, index: usize) -> io::Result<()> {
+        let frame = &self.frames[index];
+        let mut payload = &self.data[frame.offset + FRAME_HEADER_LEN..frame.offset + FRAME_HEADER_LEN + frame.payload_len];
+
+        if frame.is_keyframe {
+            self.state = (0..frame.count).map(|_| QuantizedParticle::read_raw(&mut payload)).collect::<io::Result<_>>()?;
+        } else {
+            if self.state.len() != frame.count {
+                return Err(invalid_data("Delta frame does not match previous frame"));
+            }
+            for q in self.state.iter_mut() {
+                *q = QuantizedParticle::read_delta(q, &mut payload)?;
+            }
+        }
+
+        if !payload.is_empty() {
+            return Err(invalid_data("Frame payload does not match its particle count"));
+        }
+        Ok(())
+    }
+}
+
+impl From<&Particle> for QuantizedParticle {
+    fn from(p: &Particle) -> Self {
+        QuantizedParticle {
+            color: p.color as u8,
+            pinned: p.pinned,
+            x: quantize_position(p.position.x, WORLD_WIDTH_FLOAT),
+            y: quantize_position(p.position.y, WORLD_HEIGHT_FLOAT),
+            vx: quantize_velocity(p.velocity.x),
+            vy: quantize_velocity(p.velocity.y),
+        }
+    }
+}
+
+impl From<&QuantizedParticle> for Particle {
+    fn from(q: &QuantizedParticle) -> Self {
+        Particle::new(
+            Point::new(
+                q.x as f32 / POSITION_QUANTUM_STEPS * WORLD_WIDTH_FLOAT,
+                q.y as f32 / POSITION_QUANTUM_STEPS * WORLD_HEIGHT_FLOAT,
+            ),
+            Vector::new(q.vx as f32 / VELOCITY_QUANTUM_PER_UNIT, q.vy as f32 / VELOCITY_QUANTUM_PER_UNIT),
+            ParticleColor::ALL[usize::min(q.color as usize, ParticleColor::ALL.len() - 1)],
+        ).with_pinned(q.pinned)
+    }
+}
+
+impl QuantizedParticle {
+    fn write_raw(&self, out: &mut Vec<u8>) {
+        out.push(self.color);
+        out.push(self.pinned as u8);
+        out.extend_from_slice(&self.x.to_le_bytes());
+        out.extend_from_slice(&self.y.to_le_bytes());
+        out.extend_from_slice(&self.vx.to_le_bytes());
+        out.extend_from_slice(&self.vy.to_le_bytes());
+    }
+
+    fn read_raw(input: &mut &[u8]) -> io::Result<Self> {
+        let mut buf = [0u8; 10];
+        input.read_exact(&mut buf)?;
+        Ok(QuantizedParticle {
+            color: buf[0],
+            pinned: buf[1] != 0,
+            x: u16::from_le_bytes([buf[2], buf[3]]),
+            y: u16::from_le_bytes([buf[4], buf[5]]),
+            vx: i16::from_le_bytes([buf[6], buf[7]]),
+            vy: i16::from_le_bytes([buf[8], buf[9]]),
+        })
+    }
+
+    fn write_delta(&self, prev: &Self, out: &mut Vec<u8>) {
+        write_varint(out, self.color as i32 - prev.color as i32);
+        write_varint(out, self.pinned as i32 - prev.pinned as i32);
+        write_varint(out, self.x as i32 - prev.x as i32);
+        write_varint(out, self.y as i32 - prev.y as i32);
+        write_varint(out, self.vx as i32 - prev.vx as i32);
+        write_varint(out, self.vy as i32 - prev.vy as i32);
+    }
+
+    fn read_delta(prev: &Self, input: &mut &[u8]) -> io::Result<Self> {
+        Ok(QuantizedParticle {
+            color: (prev.color as i32 + read_varint(input)?) as u8,
+            pinned: prev.pinned as i32 + read_varint(input)? != 0,
+            x: (prev.x as i32 + read_varint(input)?) as u16,
+            y: (prev.y as i32 + read_varint(input)?) as u16,
+            vx: (prev.vx as i32 + read_varint(input)?) as i16,
+            vy: (prev.vy as i32 + read_varint(input)?) as i16,
+        })
+    }
+}
+
+fn quantize_position(value: f32, world_size: f32) -> u16 {
+    if !value.is_finite() {
+        return 0;
+    }
+    (value / world_size * POSITION_QUANTUM_STEPS).round().clamp(0., POSITION_QUANTUM_STEPS) as u16
+}
+
+fn quantize_velocity(value: f32) -> i16 {
+    if !value.is_finite() {
+        return 0;
+    }
+    (value * VELOCITY_QUANTUM_PER_UNIT).round().clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+fn write_varint(out: &mut Vec<u8>, value: i32) {
+    let mut zigzag = ((value << 1) ^ (value >> 31)) as u32;
+    while zigzag >= 0x80 {
+        out.push((zigzag as u8) | 0x80);
+        zigzag >>= 7;
+    }
+    out.push(zigzag as u8);
+}
+
+fn read_varint(input: &mut &[u8]) -> io::Result<i32> {
+    let mut zigzag = 0u32;
+    for shift in (0..35).step_by(7) {
+        let Some((&byte, rest)) = input.split_first() else {
+            return Err(invalid_data("Truncated frame"));
+        };
+        *input = rest;
+        zigzag |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(((zigzag >> 1) as i32) ^ -((zigzag & 1) as i32));
+        }
+    }
+    Err(invalid_data("Malformed varint"))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,553 @@
+use std::{
+    fmt::{self, Display},
+    fs,
+    io,
+    path::Path,
+    str::FromStr,
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use crate::{
+    bond, calc, AngleConstraint, Bond, BondRule, Field, HealthPolicy, Reaction, Thermostat, ForcesConfig, Obstacle, PairForce, PairRadii, Particle, ParticleColor, PhysicsMode, PhysicsParams, Point, Vector, World,
+};
+
+// Plain-text world description, one directive per line:
+//   physics <real|emergence>
+//   law <real|emergence|lennard-jones|morse|yukawa|spring> [parameters]
+//   friction <multiplier>
+//   repel-radius <world units>
+//   max-substeps <count>
+//   max-displacement <repel radii per substep>
+//   force <who> <to> <strength>
+//   radii <who> <to> <repel> <interaction>
+//   spawn <color> <count>
+//   particle <color> <x> <y> <vx> <vy> [pinned]
+//   wall <x1> <y1> <x2> <y2>
+//   circle <x> <y> <radius>
+//   polygon <x1> <y1> <x2> <y2> <x3> <y3> ...
+//   bond <a> <b> <rest length> <stiffness> [max strain]
+//   angle <a> <center> <b> <degrees> <stiffness>
+//   bond-rule <who> <to> <distance> <stiffness> <valence> [max strain]
+//   chain <color> <count> <x1> <y1> <x2> <y2> <stiffness> [angle stiffness]
+//   react <who> <catalyst> <distance> <product> <probability per tick>
+//   decay <who> <product> <ticks>
+//   seed <number>
+//   langevin <temperature> <damping>
+//   berendsen <temperature> <time constant in ticks>
+//   health <panic|repair|remove>
+//   gravity <ax> <ay>
+//   attractor <x> <y> <strength> <softening>
+//   vortex <x> <y> <strength> <radius>
+//   shear <rate>
+//   field-grid <path> [scale]
+// Bond and angle indices count the particles from earlier particle and chain lines, chains add particles evenly
+// spaced from one end to the other, bonded one after another and kept straight when given an angle stiffness.
+// Empty lines and lines starting with '#' are ignored.
+#[derive(Clone)]
+pub struct Scenario {
+    pub physics_mode: PhysicsMode,
+    pub forces: ForcesConfig,
+    // None uses the physics mode's own law
+    pub law: Option<PairForce>,
+    pub params: PhysicsParams,
+    pub spawns: Vec<(ParticleColor, usize)>,
+    pub particles: Vec<Particle>,
+    pub obstacles: Vec<Obstacle>,
+    pub fields: Vec<Field>,
+    pub bonds: Vec<Bond>,
+    pub angles: Vec<AngleConstraint>,
+    pub bond_rules: Vec<BondRule>,
+    pub reactions: Vec<Reaction>,
+    pub thermostat: Option<Thermostat>,
+    pub health: Option<HealthPolicy>,
+    // Used by `build_world`, so the spawns and everything random while ticking come out the same every time
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl Scenario {
+    pub fn new(physics_mode: PhysicsMode, forces: ForcesConfig) -> Self {
+        Scenario {
+            physics_mode,
+            forces,
+            law: None,
+            params: PhysicsParams::default(),
+            spawns: Vec::new(),
+            particles: Vec::new(),
+            obstacles: Vec::new(),
+            fields: Vec::new(),
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            bond_rules: Vec::new(),
+            reactions: Vec::new(),
+            thermostat: None,
+            health: None,
+            seed: None,
+        }
+    }
+
+    pub fn from_world(world: &World) -> Self {
+        Scenario {
+            physics_mode: world.physics_mode,
+            forces: world.forces,
+            law: Some(world.law.clone()),
+            params: world.params,
+            spawns: Vec::new(),
+            particles: world.particles.clone(),
+            obstacles: world.obstacles.clone(),
+            fields: world.fields.clone(),
+            bonds: world.bonds().to_vec(),
+            angles: world.angle_constraints().to_vec(),
+            bond_rules: world.bond_rules().to_vec(),
+            reactions: world.reactions().to_vec(),
+            thermostat: world.thermostat(),
+            health: world.health_check(),
+            seed: None,
+        }
+    }
+
+    pub fn with_physics_params(mut self, params: PhysicsParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn with_force_law(mut self, law: PairForce) -> Self {
+        self.law = Some(law);
+        self
+    }
+
+    pub fn with_spawn(mut self, color: ParticleColor, count: usize) -> Self {
+        self.spawns.push((color, count));
+        self
+    }
+
+    pub fn with_particle(mut self, particle: Particle) -> Self {
+        self.particles.push(particle);
+        self
+    }
+
+    pub fn with_obstacle(mut self, obstacle: Obstacle) -> Self {
+        self.obstacles.push(obstacle);
+        self
+    }
+
+    pub fn with_field(mut self, field: Field) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    pub fn with_bond(mut self, bond: Bond) -> Self {
+        self.bonds.push(bond);
+        self
+    }
+
+    pub fn with_bond_rule(mut self, rule: BondRule) -> Self {
+        self.bond_rules.push(rule);
+        self
+    }
+
+    pub fn with_reaction(mut self, reaction: Reaction) -> Self {
+        self.reactions.push(reaction);
+        self
+    }
+
+    pub fn with_thermostat(mut self, thermostat: Thermostat) -> Self {
+        self.thermostat = Some(thermostat);
+        self
+    }
+
+    pub fn with_health_check(mut self, policy: HealthPolicy) -> Self {
+        self.health = Some(policy);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    // Keeps only the particles at `indices`, in that order, and the bonds between them
+    pub fn select_particles(mut self, indices: &[usize]) -> Self {
+        let mut new_index = vec![None; self.particles.len()];
+        indices.iter().enumerate().for_each(|(new, &old)| new_index[old] = Some(new));
+        self.particles = indices.iter().map(|&i| self.particles[i]).collect();
+        bond::remap(&mut self.bonds, &mut self.angles, &new_index);
+        self
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ScenarioError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn build_world(&self) -> World {
+        match self.seed {
+            Some(seed) => self.build_world_seeded(seed),
+            None => self.build_world_with(&mut rand::rng()),
+        }
+    }
+
+    pub fn build_world_seeded(&self, seed: u64) -> World {
+        self.build_world_with(&mut StdRng::seed_from_u64(seed))
+    }
+
+    fn build_world_with<R: Rng + ?Sized>(&self, rng: &mut R) -> World {
+        let mut particles = self.particles.clone();
+
+        for &(color, count) in self.spawns.iter() {
+            particles.extend((0..count).map(|_| Particle::new(calc::random_world_position_with(rng), Vector::new(0., 0.), color)));
+        }
+
+        let world = World::new(particles, self.forces, self.physics_mode)
+            .with_physics_params(self.params)
+            .with_obstacles(self.obstacles.clone())
+            .with_fields(self.fields.clone())
+            .with_bonds(self.bonds.clone())
+            .with_angle_constraints(self.angles.clone())
+            .with_bond_rules(self.bond_rules.clone())
+            .with_reactions(self.reactions.clone())
+            .with_thermostat(self.thermostat)
+            .with_health_check(self.health)
+            .with_seed(rng.random());
+        match self.law.clone() {
+            Some(law) => world.with_force_law(law),
+            None => world,
+        }
+    }
+}
+
+impl FromStr for Scenario {
+    type Err = ScenarioError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut scenario = Scenario::new(PhysicsMode::Emergence, ForcesConfig::empty());
+
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parse_error = |message: String| ScenarioError::Parse { line: i + 1, message };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            match (fields[0], fields.len()) {
+                ("physics", 2) => {
+                    scenario.physics_mode = PhysicsMode::from_name(fields[1])
+                        .ok_or_else(|| parse_error(format!("Unknown physics mode '{}'", fields[1])))?;
+                },
+                ("law", count) if count >= 2 => {
+                    let values = parse_numbers(&fields[2..]).map_err(parse_error)?;
+                    scenario.law = Some(PairForce::from_parts(fields[1], &values).map_err(parse_error)?);
+                },
+                ("friction", 2) => {
+                    scenario.params.friction = parse_number(fields[1]).map_err(parse_error)?;
+                },
+                ("repel-radius", 2) => {
+                    scenario.params.repel_radius = parse_number(fields[1]).map_err(parse_error)?;
+                },
+                ("max-substeps", 2) => {
+                    scenario.params = scenario.params.with_max_substeps(parse_number(fields[1]).map_err(parse_error)?);
+                },
+                ("max-displacement", 2) => {
+                    scenario.params.max_displacement = parse_number(fields[1]).map_err(parse_error)?;
+                },
+                ("force", 4) => {
+                    let who = parse_color(fields[1]).map_err(parse_error)?;
+                    let to = parse_color(fields[2]).map_err(parse_error)?;
+                    scenario.forces = scenario.forces.with_force(who, to, parse_number(fields[3]).map_err(parse_error)?);
+                },
+                ("radii", 5) => {
+                    let who = parse_color(fields[1]).map_err(parse_error)?;
+                    let to = parse_color(fields[2]).map_err(parse_error)?;
+                    let values = parse_numbers(&fields[3..]).map_err(parse_error)?;
+                    if !(values[0] > 0. && values[0] < values[1]) {
+                        return Err(parse_error("Radii need 0 < repel < interaction".to_string()));
+                    }
+                    scenario.forces = scenario.forces.with_radii(who, to, PairRadii { repel: values[0], interaction: values[1] });
+                },
+                ("spawn", 3) => {
+                    let color = parse_color(fields[1]).map_err(parse_error)?;
+                    scenario.spawns.push((color, parse_number(fields[2]).map_err(parse_error)?));
+                },
+                ("particle", 6 | 7) => {
+                    let color = parse_color(fields[1]).map_err(parse_error)?;
+                    let pinned = match fields.get(6) {
+                        None => false,
+                        Some(&"pinned") => true,
+                        Some(flag) => return Err(parse_error(format!("Unknown particle flag '{}'", flag))),
+                    };
+                    let values = parse_numbers(&fields[2..6]).map_err(parse_error)?;
+                    let particle = Particle::new(Point::new(values[0], values[1]), Vector::new(values[2], values[3]), color);
+                    scenario.particles.push(particle.with_pinned(pinned));
+                },
+                ("wall", 5) => {
+                    let values = parse_numbers(&fields[1..]).map_err(parse_error)?;
+                    scenario.obstacles.push(Obstacle::Wall { from: Point::new(values[0], values[1]), to: Point::new(values[2], values[3]) });
+                },
+                ("circle", 4) => {
+                    let values = parse_numbers(&fields[1..]).map_err(parse_error)?;
+                    scenario.obstacles.push(Obstacle::Circle { center: Point::new(values[0], values[1]), radius: values[2] });
+                },
+                ("polygon", count) if count >= 7 && count % 2 == 1 => {
+                    let values = parse_numbers(&fields[1..]).map_err(parse_error)?;
+                    let points = values.chunks(2).map(|xy| Point::new(xy[0], xy[1])).collect();
+                    scenario.obstacles.push(Obstacle::Polygon { points });
+                },
+                ("gravity", 3) => {
+                    let values = parse_numbers(&fields[1..]).map_err(parse_error)?;
+                    scenario.fields.push(Field::Gravity { acceleration: Vector::new(values[0], values[1]) });
+                },
+                ("attractor", 5) => {
+                    let values = parse_numbers(&fields[1..]).map_err(parse_error)?;
+                    scenario.fields.push(Field::Attractor { center: Point::new(values[0], values[1]), strength: values[2], softening: values[3] });
+                },
+                ("vortex", 5) => {
+                    let values = parse_numbers(&fields[1..]).map_err(parse_error)?;
+                    scenario.fields.push(Field::Vortex { center: Point::new(values[0], values[1]), strength: values[2], radius: values[3] });
+                },
+                ("shear", 2) => {
+                    scenario.fields.push(Field::Shear { rate: parse_number(fields[1]).map_err(parse_error)? });
+                },
+                ("field-grid", 2 | 3) => {
+                    let scale = fields.get(2).map_or(Ok(1.), |scale| parse_number(scale)).map_err(parse_error)?;
+                    let field = Field::grid(fields[1], scale)
+                        .map_err(|e| parse_error(format!("Could not load vector grid '{}': {}", fields[1], e)))?;
+                    scenario.fields.push(field);
+                },
+                ("bond", 5 | 6) => {
+                    let (a, b) = (parse_index(fields[1], scenario.particles.len()).map_err(parse_error)?, parse_index(fields[2], scenario.particles.len()).map_err(parse_error)?);
+                    let values = parse_numbers(&fields[3..]).map_err(parse_error)?;
+                    if a == b || values[0] <= 0. {
+                        return Err(parse_error("A bond needs two different particles and a positive rest length".to_string()));
+                    }
+                    scenario.bonds.push(Bond { a, b, rest_length: values[0], stiffness: values[1], max_strain: values.get(2).copied() });
+                },
+                ("angle", 6) => {
+                    let (a, center, b) = (parse_index(fields[1], scenario.particles.len()).map_err(parse_error)?, parse_index(fields[2], scenario.particles.len()).map_err(parse_error)?, parse_index(fields[3], scenario.particles.len()).map_err(parse_error)?);
+                    let values = parse_numbers(&fields[4..]).map_err(parse_error)?;
+                    scenario.angles.push(AngleConstraint { a, center, b, angle: values[0].to_radians(), stiffness: values[1] });
+                },
+                ("bond-rule", 6 | 7) => {
+                    let who = parse_color(fields[1]).map_err(parse_error)?;
+                    let to = parse_color(fields[2]).map_err(parse_error)?;
+                    let values = parse_numbers(&[fields[3], fields[4]]).map_err(parse_error)?;
+                    let valence = parse_number(fields[5]).map_err(parse_error)?;
+                    let max_strain = fields.get(6).map(|value| parse_number(value)).transpose().map_err(parse_error)?;
+                    if values[0] <= 0. {
+                        return Err(parse_error("A bond rule needs a positive distance".to_string()));
+                    }
+                    scenario.bond_rules.push(BondRule { who, to, distance: values[0], stiffness: values[1], valence, max_strain });
+                },
+                ("chain", 8 | 9) => {
+                    let color = parse_color(fields[1]).map_err(parse_error)?;
+                    let count: usize = parse_number(fields[2]).map_err(parse_error)?;
+                    let values = parse_numbers(&fields[3..]).map_err(parse_error)?;
+                    if count < 2 {
+                        return Err(parse_error("A chain needs at least 2 particles".to_string()));
+                    }
+                    let (from, to) = (Point::new(values[0], values[1]), Point::new(values[2], values[3]));
+                    if from == to {
+                        return Err(parse_error("A chain needs two different ends".to_string()));
+                    }
+
+                    let first = scenario.particles.len();
+                    let spacing = calc::world_distance(&from, &to) / (count - 1) as f32;
+                    scenario.particles.extend((0..count).map(|i| {
+                        Particle::new(from.lerp(to, i as f32 / (count - 1) as f32), Vector::new(0., 0.), color)
+                    }));
+                    scenario.bonds.extend((first..first + count - 1).map(|i| Bond::new(i, i + 1, spacing, values[4])));
+                    if let Some(&stiffness) = values.get(5) {
+                        scenario.angles.extend((first + 1..first + count - 1).map(|i| {
+                            AngleConstraint { a: i - 1, center: i, b: i + 1, angle: std::f32::consts::PI, stiffness }
+                        }));
+                    }
+                },
+                ("react", 6) => {
+                    let who = parse_color(fields[1]).map_err(parse_error)?;
+                    let catalyst = parse_color(fields[2]).map_err(parse_error)?;
+                    let distance = parse_number(fields[3]).map_err(parse_error)?;
+                    let product = parse_color(fields[4]).map_err(parse_error)?;
+                    let probability = parse_number(fields[5]).map_err(parse_error)?;
+                    if who == product || distance <= 0. || !(0. ..=1.).contains(&probability) {
+                        return Err(parse_error("A reaction needs a different product, a positive distance and a probability between 0 and 1".to_string()));
+                    }
+                    scenario.reactions.push(Reaction::Contact { who, catalyst, distance, product, probability });
+                },
+                ("decay", 4) => {
+                    let who = parse_color(fields[1]).map_err(parse_error)?;
+                    let product = parse_color(fields[2]).map_err(parse_error)?;
+                    if who == product {
+                        return Err(parse_error("A decay needs a different product".to_string()));
+                    }
+                    scenario.reactions.push(Reaction::Decay { who, product, ticks: parse_number(fields[3]).map_err(parse_error)? });
+                },
+                ("langevin", 3) => {
+                    let values = parse_numbers(&fields[1..]).map_err(parse_error)?;
+                    scenario.thermostat = Some(Thermostat::langevin(values[0], values[1]).map_err(parse_error)?);
+                },
+                ("berendsen", 3) => {
+                    let values = parse_numbers(&fields[1..]).map_err(parse_error)?;
+                    scenario.thermostat = Some(Thermostat::berendsen(values[0], values[1]).map_err(parse_error)?);
+                },
+                ("health", 2) => {
+                    scenario.health = Some(HealthPolicy::from_name(fields[1])
+                        .ok_or_else(|| parse_error(format!("Unknown health policy '{}'", fields[1])))?);
+                },
+                ("seed", 2) => {
+                    scenario.seed = Some(parse_number(fields[1]).map_err(parse_error)?);
+                },
+                (directive, _) => return Err(parse_error(format!("Invalid directive '{}'", directive))),
+            }
+        }
+
+        Ok(scenario)
+    }
+}
+
+impl Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "physics {}", self.physics_mode.name())?;
+        match &self.law {
+            None => {},
+            // Code can't be written out, whoever loads this has to set it again
+            Some(PairForce::Custom(_)) => writeln!(f, "# custom force law omitted")?,
+            Some(law) => {
+                write!(f, "law {}", law.name())?;
+                law.parameters().iter().try_for_each(|value| write!(f, " {}", value))?;
+                writeln!(f)?;
+            },
+        }
+        writeln!(f, "friction {}", self.params.friction)?;
+        writeln!(f, "repel-radius {}", self.params.repel_radius)?;
+        writeln!(f, "max-substeps {}", self.params.max_substeps)?;
+        writeln!(f, "max-displacement {}", self.params.max_displacement)?;
+
+        for &who in ParticleColor::ALL {
+            for &to in ParticleColor::ALL {
+                writeln!(f, "force {} {} {}", who.name(), to.name(), self.forces.get(who, to))?;
+                if let Some(radii) = self.forces.get_radii(who, to) {
+                    writeln!(f, "radii {} {} {} {}", who.name(), to.name(), radii.repel, radii.interaction)?;
+                }
+            }
+        }
+
+        for (color, count) in self.spawns.iter() {
+            writeln!(f, "spawn {} {}", color.name(), count)?;
+        }
+
+        for p in self.particles.iter() {
+            write!(f, "particle {} {} {} {} {}", p.color.name(), p.position.x, p.position.y, p.velocity.x, p.velocity.y)?;
+            writeln!(f, "{}", if p.pinned { " pinned" } else { "" })?;
+        }
+
+        for obstacle in self.obstacles.iter() {
+            match obstacle {
+                Obstacle::Wall { from, to } => writeln!(f, "wall {} {} {} {}", from.x, from.y, to.x, to.y)?,
+                Obstacle::Circle { center, radius } => writeln!(f, "circle {} {} {}", center.x, center.y, radius)?,
+                Obstacle::Polygon { points } => {
+                    write!(f, "polygon")?;
+                    points.iter().try_for_each(|p| write!(f, " {} {}", p.x, p.y))?;
+                    writeln!(f)?;
+                },
+            }
+        }
+
+        for bond in self.bonds.iter() {
+            write!(f, "bond {} {} {} {}", bond.a, bond.b, bond.rest_length, bond.stiffness)?;
+            bond.max_strain.map_or(Ok(()), |max_strain| write!(f, " {}", max_strain))?;
+            writeln!(f)?;
+        }
+
+        for c in self.angles.iter() {
+            writeln!(f, "angle {} {} {} {} {}", c.a, c.center, c.b, c.angle.to_degrees(), c.stiffness)?;
+        }
+
+        for rule in self.bond_rules.iter() {
+            write!(f, "bond-rule {} {} {} {} {}", rule.who.name(), rule.to.name(), rule.distance, rule.stiffness, rule.valence)?;
+            rule.max_strain.map_or(Ok(()), |max_strain| write!(f, " {}", max_strain))?;
+            writeln!(f)?;
+        }
+
+        for reaction in self.reactions.iter() {
+            match reaction {
+                Reaction::Contact { who, catalyst, distance, product, probability } => {
+                    writeln!(f, "react {} {} {} {} {}", who.name(), catalyst.name(), distance, product.name(), probability)?;
+                },
+                Reaction::Decay { who, product, ticks } => writeln!(f, "decay {} {} {}", who.name(), product.name(), ticks)?,
+            }
+        }
+
+        match self.thermostat {
+            Some(Thermostat::Langevin { temperature, damping }) => writeln!(f, "langevin {} {}", temperature, damping)?,
+            Some(Thermostat::Berendsen { temperature, time_constant }) => writeln!(f, "berendsen {} {}", temperature, time_constant)?,
+            None => {},
+        }
+
+        if let Some(policy) = self.health {
+            writeln!(f, "health {}", policy.name())?;
+        }
+
+        if let Some(seed) = self.seed {
+            writeln!(f, "seed {}", seed)?;
+        }
+
+        for field in self.fields.iter() {
+            match field {
+                Field::Gravity { acceleration } => writeln!(f, "gravity {} {}", acceleration.x, acceleration.y)?,
+                Field::Attractor { center, strength, softening } => writeln!(f, "attractor {} {} {} {}", center.x, center.y, strength, softening)?,
+                Field::Vortex { center, strength, radius } => writeln!(f, "vortex {} {} {} {}", center.x, center.y, strength, radius)?,
+                Field::Shear { rate } => writeln!(f, "shear {}", rate)?,
+                Field::Grid { path, scale, .. } => writeln!(f, "field-grid {} {}", path.display(), scale)?,
+                // Code can't be written out, whoever loads this has to add it again
+                Field::Custom(_) => writeln!(f, "# custom field omitted")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Io(e) => write!(f, "Could not read scenario: {}", e),
+            ScenarioError::Parse { line, message } => write!(f, "Scenario line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl From<io::Error> for ScenarioError {
+    fn from(e: io::Error) -> Self {
+        ScenarioError::Io(e)
+    }
+}
+
+fn parse_color(name: &str) -> Result<ParticleColor, String> {
+    ParticleColor::from_name(name).ok_or_else(|| format!("Unknown color '{}'", name))
+}
+
+fn parse_number<T: FromStr>(value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Invalid number '{}'", value))
+}
+
+fn parse_index(value: &str, particles_count: usize) -> Result<usize, String> {
+    let index = parse_number(value)?;
+    if index >= particles_count {
+        return Err(format!("No particle {} yet, there are {} so far", index, particles_count));
+    }
+    Ok(index)
+}
+
+fn parse_numbers(values: &[&str]) -> Result<Vec<f32>, String> {
+    values.iter().map(|v| parse_number(v)).collect()
+}
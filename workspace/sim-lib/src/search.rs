@@ -0,0 +1,242 @@
+use std::sync::Arc;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use crate::{
+    analysis, CalculationMethod, ForcesConfig, Particle, ParticleColor, PhysicsMode, Scenario, ThreadPool, World,
+};
+
+pub trait Fitness: Send + Sync {
+    fn name(&self) -> &str;
+    fn score(&self, initial: &[Particle], world: &World) -> f32;
+}
+
+pub struct ClusterCount {
+    pub radius: f32,
+    pub min_size: usize,
+}
+
+pub struct Motility;
+
+pub struct KineticEnergy;
+
+pub struct SearchConfig {
+    pub population: usize,
+    pub generations: usize,
+    pub elite_count: usize,
+    pub tournament_size: usize,
+    pub particles_per_species: usize,
+    pub ticks: u32,
+    pub physics_mode: PhysicsMode,
+    pub min_force: f32,
+    pub max_force: f32,
+    pub mutation_rate: f32,
+    pub mutation_strength: f32,
+    pub seed: u64,
+}
+
+#[derive(Copy, Clone)]
+pub struct Candidate {
+    pub forces: ForcesConfig,
+    pub score: f32,
+}
+
+pub struct GeneticSearch {
+    config: SearchConfig,
+    objectives: Vec<(Arc<dyn Fitness>, f32)>,
+}
+
+impl Fitness for ClusterCount {
+    fn name(&self) -> &str {
+        "cluster"
+    }
+
+    fn score(&self, _initial: &[Particle], world: &World) -> f32 {
+        analysis::cluster_count(&world.particles, self.radius, self.min_size) as f32
+    }
+}
+
+impl Fitness for Motility {
+    fn name(&self) -> &str {
+        "motility"
+    }
+
+    fn score(&self, initial: &[Particle], world: &World) -> f32 {
+        analysis::mean_displacement(initial, &world.particles)
+    }
+}
+
+impl Fitness for KineticEnergy {
+    fn name(&self) -> &str {
+        "energy"
+    }
+
+    fn score(&self, _initial: &[Particle], world: &World) -> f32 {
+        analysis::mean_kinetic_energy(&world.particles)
+    }
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            population: 32,
+            generations: 20,
+            elite_count: 4,
+            tournament_size: 3,
+            particles_per_species: 300,
+            ticks: 300,
+            physics_mode: PhysicsMode::Emergence,
+            min_force: -0.3,
+            max_force: 1.0,
+            mutation_rate: 0.2,
+            mutation_strength: 0.2,
+            seed: 0,
+        }
+    }
+}
+
+impl SearchConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.population == 0 {
+            return Err("The population needs at least one candidate".to_string());
+        }
+        if !(self.min_force.is_finite() && self.max_force.is_finite() && self.min_force < self.max_force) {
+            return Err(format!("Expected finite forces with min below max, got {} to {}", self.min_force, self.max_force));
+        }
+        if !(0. ..=1.).contains(&self.mutation_rate) {
+            return Err(format!("Mutation rate has to be between 0 and 1, got {}", self.mutation_rate));
+        }
+        if !self.mutation_strength.is_finite() || self.mutation_strength < 0. {
+            return Err(format!("Mutation strength has to be finite and not negative, got {}", self.mutation_strength));
+        }
+        Ok(())
+    }
+}
+
+impl Candidate {
+    // The world it was scored in, seed included, so running the scenario for `ticks` gets the same score again
+    pub fn to_scenario(&self, config: &SearchConfig) -> Scenario {
+        ParticleColor::ALL.iter().fold(Scenario::new(config.physics_mode, self.forces), |s, &color| {
+            s.with_spawn(color, config.particles_per_species)
+        }).with_seed(config.seed)
+    }
+}
+
+impl GeneticSearch {
+    pub fn new(config: SearchConfig) -> Result<Self, String> {
+        config.validate()?;
+        Ok(GeneticSearch {
+            config,
+            objectives: Vec::new(),
+        })
+    }
+
+    pub fn with_objective<F: Fitness + 'static>(mut self, fitness: F, weight: f32) -> Self {
+        self.objectives.push((Arc::new(fitness), weight));
+        self
+    }
+
+    pub fn config(&self) -> &SearchConfig {
+        &self.config
+    }
+
+    // Returns the last generation sorted from best to worst. `on_generation` sees every scored generation in the same order.
+    pub fn run<F: FnMut(usize, &[Candidate])>(&self, thread_pool: &ThreadPool, mut on_generation: F) -> Vec<Candidate> {
+        assert!(!self.objectives.is_empty(), "At least one fitness objective is required");
+
+        let mut rng = StdRng::seed_from_u64(self.config.seed);
+        let mut population: Vec<ForcesConfig> = (0..self.config.population)
+            .map(|_| ForcesConfig::random_with(&mut rng, self.config.min_force, self.config.max_force))
+            .collect();
+
+        let mut generation = 0;
+        loop {
+            let mut scored = self.evaluate(thread_pool, &population);
+            scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+            on_generation(generation, &scored);
+
+            generation += 1;
+            if generation >= self.config.generations {
+                return scored;
+            }
+
+            population = self.next_generation(&mut rng, &scored);
+        }
+    }
+
+    fn evaluate(&self, thread_pool: &ThreadPool, population: &[ForcesConfig]) -> Vec<Candidate> {
+        let (sender, receiver) = crossbeam_channel::bounded(population.len());
+        let scenario = Candidate { forces: ForcesConfig::empty(), score: 0. }.to_scenario(&self.config);
+        // Every candidate of every generation starts from the same layout and draws the same respawns, so scores
+        // stay comparable and the same seed always picks the same winner
+        let initial = Arc::new(scenario.build_world().particles);
+
+        population.iter().enumerate().for_each(|(i, &forces)| {
+            let sender = sender.clone();
+            let initial = initial.clone();
+            let objectives = self.objectives.clone();
+            let ticks = self.config.ticks;
+            let scenario = Scenario { forces, ..scenario.clone() };
+
+            thread_pool.execute(move || {
+                let mut world = scenario.build_world();
+                for _ in 0..ticks {
+                    world.tick(&CalculationMethod::SingleThread, 1.);
+                }
+
+                let score = objectives.iter().map(|(fitness, weight)| weight * fitness.score(&initial, &world)).sum();
+                sender.send((i, Candidate { forces, score })).expect("Results channel will be there waiting for the pool");
+            });
+        });
+
+        let mut results: Vec<(usize, Candidate)> = receiver.iter().take(population.len()).collect();
+        results.sort_by_key(|(i, _)| *i);
+        results.into_iter().map(|(_, c)| c).collect()
+    }
+
+    fn next_generation(&self, rng: &mut StdRng, ranked: &[Candidate]) -> Vec<ForcesConfig> {
+        let elites = ranked.iter().take(self.config.elite_count).map(|c| c.forces);
+
+        let children: Vec<ForcesConfig> = (elites.len()..self.config.population).map(|_| {
+            let a = self.tournament(rng, ranked);
+            let b = self.tournament(rng, ranked);
+            let child = Self::crossover(rng, a, b);
+            self.mutate(rng, child)
+        }).collect();
+
+        elites.chain(children).collect()
+    }
+
+    fn tournament(&self, rng: &mut StdRng, ranked: &[Candidate]) -> ForcesConfig {
+        // `ranked` is sorted best first, so the lowest drawn index wins
+        let winner = (0..usize::max(self.config.tournament_size, 1))
+            .map(|_| rng.random_range(0..ranked.len()))
+            .min()
+            .unwrap_or(0);
+        ranked[winner].forces
+    }
+
+    fn crossover(rng: &mut StdRng, a: ForcesConfig, b: ForcesConfig) -> ForcesConfig {
+        Self::cells().fold(a, |child, (who, to)| {
+            if rng.random_bool(0.5) {
+                child.with_force_unchecked(who, to, b.get_unchecked(who, to))
+            } else {
+                child
+            }
+        })
+    }
+
+    fn mutate(&self, rng: &mut StdRng, forces: ForcesConfig) -> ForcesConfig {
+        Self::cells().fold(forces, |mutated, (who, to)| {
+            if !rng.random_bool(self.config.mutation_rate as f64) {
+                return mutated;
+            }
+            let delta = rng.random_range(-self.config.mutation_strength..=self.config.mutation_strength);
+            let force = (mutated.get_unchecked(who, to) + delta).clamp(self.config.min_force, self.config.max_force);
+            mutated.with_force_unchecked(who, to, force)
+        })
+    }
+
+    fn cells() -> impl Iterator<Item = (usize, usize)> {
+        (0..ParticleColor::matrix_len() * ParticleColor::matrix_len())
+            .map(|i| (i / ParticleColor::matrix_len(), i % ParticleColor::matrix_len()))
+    }
+}
@@ -0,0 +1,201 @@
+use std::io::{self, Write};
+use crate::{
+    analysis, CalculationMethod, ParticleColor, Scenario, Thermostat, ThreadPool,
+};
+
+#[derive(Clone)]
+pub enum SweepAxis {
+    Force { who: ParticleColor, to: ParticleColor, values: Vec<f32> },
+    // No effect on scenarios with a thermostat, or on runs along a temperature axis
+    Friction(Vec<f32>),
+    RepelRadius(Vec<f32>),
+    // Target of the scenario's thermostat, a Langevin one with the default damping if it has none. Either way the
+    // thermostat replaces friction, so runs end up at this temperature.
+    Temperature(Vec<f32>),
+    ParticlesPerSpecies(Vec<usize>),
+}
+
+pub struct Sweep {
+    base: Scenario,
+    axes: Vec<SweepAxis>,
+    seeds: Vec<u64>,
+    ticks: u32,
+    cluster_radius: f32,
+    cluster_min_size: usize,
+}
+
+pub struct SweepRun {
+    pub index: usize,
+    pub seed: u64,
+    pub parameters: Vec<f32>,
+    pub metrics: SweepMetrics,
+}
+
+#[derive(Copy, Clone)]
+pub struct SweepMetrics {
+    pub kinetic_energy: f32,
+    pub mean_displacement: f32,
+    pub cluster_count: usize,
+}
+
+impl SweepAxis {
+    pub fn linspace(start: f32, end: f32, steps: usize) -> Vec<f32> {
+        match steps {
+            0 => Vec::new(),
+            1 => vec![start],
+            _ => (0..steps).map(|i| start + (end - start) * i as f32 / (steps - 1) as f32).collect(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            SweepAxis::Force { who, to, .. } => format!("force_{}_{}", who.name(), to.name()),
+            SweepAxis::Friction(_) => "friction".to_string(),
+            SweepAxis::RepelRadius(_) => "repel_radius".to_string(),
+            SweepAxis::Temperature(_) => "temperature".to_string(),
+            SweepAxis::ParticlesPerSpecies(_) => "particles_per_species".to_string(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            SweepAxis::Force { values, .. } | SweepAxis::Friction(values) | SweepAxis::RepelRadius(values)
+            | SweepAxis::Temperature(values) => values.len(),
+            SweepAxis::ParticlesPerSpecies(counts) => counts.len(),
+        }
+    }
+
+    fn apply(&self, scenario: &mut Scenario, i: usize) -> f32 {
+        match self {
+            SweepAxis::Force { who, to, values } => {
+                scenario.forces = scenario.forces.with_force(*who, *to, values[i]);
+                values[i]
+            },
+            SweepAxis::Friction(values) => {
+                scenario.params.friction = values[i];
+                values[i]
+            },
+            SweepAxis::RepelRadius(values) => {
+                scenario.params.repel_radius = values[i];
+                values[i]
+            },
+            SweepAxis::Temperature(values) => {
+                scenario.thermostat = Some(scenario.thermostat.map_or(Thermostat::at(values[i]), |t| t.with_temperature(values[i])));
+                values[i]
+            },
+            SweepAxis::ParticlesPerSpecies(counts) => {
+                scenario.spawns = ParticleColor::ALL.iter().map(|&color| (color, counts[i])).collect();
+                counts[i] as f32
+            },
+        }
+    }
+}
+
+impl Sweep {
+    pub fn new(base: Scenario, ticks: u32) -> Self {
+        Sweep {
+            base,
+            axes: Vec::new(),
+            seeds: vec![0],
+            ticks,
+            cluster_radius: 0.5,
+            cluster_min_size: 5,
+        }
+    }
+
+    pub fn with_axis(mut self, axis: SweepAxis) -> Self {
+        self.axes.push(axis);
+        self
+    }
+
+    pub fn with_seeds(mut self, seeds: Vec<u64>) -> Self {
+        self.seeds = seeds;
+        self
+    }
+
+    pub fn with_cluster_params(mut self, radius: f32, min_size: usize) -> Self {
+        assert!(radius > 0.);
+        self.cluster_radius = radius;
+        self.cluster_min_size = min_size;
+        self
+    }
+
+    pub fn runs_count(&self) -> usize {
+        self.axes.iter().map(SweepAxis::len).product::<usize>() * self.seeds.len()
+    }
+
+    // Runs every combination of axis values for every seed, calling `on_run` in run index order. Each run draws
+    // everything random from its seed, so the same sweep writes the same rows every time.
+    pub fn run<F: FnMut(&SweepRun)>(&self, thread_pool: &ThreadPool, mut on_run: F) {
+        let total = self.runs_count();
+        let (sender, receiver) = crossbeam_channel::bounded(total);
+
+        for index in 0..total {
+            let (scenario, parameters, seed) = self.configuration(index);
+            let sender = sender.clone();
+            let ticks = self.ticks;
+            let (cluster_radius, cluster_min_size) = (self.cluster_radius, self.cluster_min_size);
+
+            thread_pool.execute(move || {
+                let mut world = scenario.build_world_seeded(seed);
+                let initial = world.particles.clone();
+                for _ in 0..ticks {
+                    world.tick(&CalculationMethod::SingleThread, 1.);
+                }
+
+                let metrics = SweepMetrics {
+                    kinetic_energy: analysis::mean_kinetic_energy(&world.particles),
+                    mean_displacement: analysis::mean_displacement(&initial, &world.particles),
+                    cluster_count: analysis::cluster_count(&world.particles, cluster_radius, cluster_min_size),
+                };
+
+                sender.send(SweepRun { index, seed, parameters, metrics }).expect("Results channel will be there waiting for the pool");
+            });
+        }
+
+        // Runs finish out of order, hold the early ones back until their predecessors arrive
+        let mut pending: Vec<Option<SweepRun>> = (0..total).map(|_| None).collect();
+        let mut next = 0;
+        for run in receiver.iter().take(total) {
+            let index = run.index;
+            pending[index] = Some(run);
+            while next < total {
+                let Some(run) = pending[next].take() else {
+                    break;
+                };
+                on_run(&run);
+                next += 1;
+            }
+        }
+    }
+
+    pub fn write_csv_header<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "run,seed")?;
+        for axis in self.axes.iter() {
+            write!(writer, ",{}", axis.name())?;
+        }
+        writeln!(writer, ",kinetic_energy,mean_displacement,cluster_count")
+    }
+
+    pub fn write_csv_row<W: Write>(&self, mut writer: W, run: &SweepRun) -> io::Result<()> {
+        write!(writer, "{},{}", run.index, run.seed)?;
+        for value in run.parameters.iter() {
+            write!(writer, ",{}", value)?;
+        }
+        writeln!(writer, ",{},{},{}", run.metrics.kinetic_energy, run.metrics.mean_displacement, run.metrics.cluster_count)
+    }
+
+    fn configuration(&self, index: usize) -> (Scenario, Vec<f32>, u64) {
+        let mut scenario = self.base.clone();
+        let mut remainder = index / self.seeds.len();
+        let seed = self.seeds[index % self.seeds.len()];
+
+        let parameters = self.axes.iter().rev().map(|axis| {
+            let i = remainder % axis.len();
+            remainder /= axis.len();
+            axis.apply(&mut scenario, i)
+        }).collect::<Vec<f32>>().into_iter().rev().collect();
+
+        (scenario, parameters, seed)
+    }
+}
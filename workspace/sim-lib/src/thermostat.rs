@@ -0,0 +1,98 @@
+use std::f32::consts::TAU;
+use rand::Rng;
+use crate::{Particle, Vector};
+
+// Damping used when a temperature is asked for without saying which thermostat, see `Thermostat::at`
+pub const DEFAULT_LANGEVIN_DAMPING: f32 = 0.1;
+
+// Holds the temperature of the world, measured as the mean kinetic energy per particle like
+// `mean_kinetic_energy`, with unit masses and velocities in pixels per tick. Pinned particles are left out.
+// Emergence friction is off while a thermostat is set, the thermostat takes over damping the world.
+// Build one through `langevin` or `berendsen` to have its parameters checked.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Thermostat {
+    // Every particle loses `damping` of its velocity per tick and gets a matching random kick, so it samples the
+    // target temperature on its own and adds Brownian motion
+    Langevin { temperature: f32, damping: f32 },
+    // Rescales all velocities together so the temperature relaxes toward the target over `time_constant` ticks,
+    // without adding any noise, so a world completely at rest stays that way
+    Berendsen { temperature: f32, time_constant: f32 },
+}
+
+impl Thermostat {
+    pub fn at(temperature: f32) -> Self {
+        Thermostat::Langevin { temperature, damping: DEFAULT_LANGEVIN_DAMPING }
+    }
+
+    pub fn langevin(temperature: f32, damping: f32) -> Result<Self, String> {
+        let thermostat = Thermostat::Langevin { temperature, damping };
+        thermostat.validate()?;
+        Ok(thermostat)
+    }
+
+    pub fn berendsen(temperature: f32, time_constant: f32) -> Result<Self, String> {
+        let thermostat = Thermostat::Berendsen { temperature, time_constant };
+        thermostat.validate()?;
+        Ok(thermostat)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.temperature().is_finite() || self.temperature() < 0. {
+            return Err(format!("The temperature must be a finite number of at least 0, got {}", self.temperature()));
+        }
+        match *self {
+            Thermostat::Langevin { damping, .. } if !(0. ..=1.).contains(&damping) =>
+                Err(format!("The Langevin damping must be between 0 and 1, got {}", damping)),
+            // Relaxing faster than a tick overshoots the target and can flip the world to a standstill
+            Thermostat::Berendsen { time_constant, .. } if !time_constant.is_finite() || time_constant < 1. =>
+                Err(format!("The Berendsen time constant must be a finite number of at least 1 tick, got {}", time_constant)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn temperature(&self) -> f32 {
+        match self {
+            Thermostat::Langevin { temperature, .. } | Thermostat::Berendsen { temperature, .. } => *temperature,
+        }
+    }
+
+    pub fn with_temperature(mut self, target: f32) -> Self {
+        match &mut self {
+            Thermostat::Langevin { temperature, .. } | Thermostat::Berendsen { temperature, .. } => *temperature = target,
+        }
+        self
+    }
+
+    pub(crate) fn apply<R: Rng + ?Sized>(&self, particles: &mut [Particle], dt: f32, rng: &mut R) {
+        match *self {
+            Thermostat::Langevin { temperature, damping } => {
+                // Exact for a free particle over `dt` ticks, stays stable for any damping between 0 and 1
+                let decay = (1. - damping).powf(dt);
+                let kick = f32::sqrt(temperature.max(0.) * (1. - decay * decay));
+                for particle in particles.iter_mut().filter(|p| !p.pinned) {
+                    let (x, y) = standard_normal_pair(rng);
+                    particle.velocity = particle.velocity * decay + Vector::new(x, y) * kick;
+                }
+            },
+            Thermostat::Berendsen { temperature, time_constant } => {
+                let (energy, count) = particles.iter()
+                    .filter(|p| !p.pinned)
+                    .fold((0., 0), |(energy, count), p| (energy + 0.5 * p.velocity.square_length(), count + 1));
+                if count == 0 || energy == 0. {
+                    return;
+                }
+
+                let current = energy / count as f32;
+                let scale = f32::sqrt((1. + (temperature.max(0.) / current - 1.) * dt / time_constant).max(0.));
+                particles.iter_mut().filter(|p| !p.pinned).for_each(|p| p.velocity *= scale);
+            },
+        }
+    }
+}
+
+// Box-Muller, rand itself has no normal distribution
+fn standard_normal_pair<R: Rng + ?Sized>(rng: &mut R) -> (f32, f32) {
+    let radius = f32::sqrt(-2. * (1. - rng.random::<f32>()).ln());
+    let (sin, cos) = (TAU * rng.random::<f32>()).sin_cos();
+    (radius * cos, radius * sin)
+}
@@ -0,0 +1,32 @@
+// Bonds handed to a world have to survive a tick whatever they point at
+
+use sim_lib::{AngleConstraint, Bond, BondRule, CalculationMethod, ForcesConfig, Particle, ParticleColor, PhysicsMode, Point, Vector, World};
+
+fn pair() -> Vec<Particle> {
+    [Point::new(1000., 1000.), Point::new(1050., 1000.)].into_iter()
+        .map(|position| Particle::new(position, Vector::zero(), ParticleColor::Red))
+        .collect()
+}
+
+#[test]
+fn builders_drop_missing_particles() {
+    let mut world = World::new(pair(), ForcesConfig::empty(), PhysicsMode::Emergence)
+        .with_bonds(vec![Bond::new(0, 1, 0.5, 1.), Bond::new(0, 5, 0.5, 1.)])
+        .with_angle_constraints(vec![AngleConstraint { a: 0, center: 1, b: 7, angle: 0., stiffness: 1. }]);
+    world.tick(&CalculationMethod::SingleThread, 1.);
+
+    assert_eq!(world.bonds().len(), 1);
+    assert!(world.angle_constraints().is_empty());
+}
+
+#[test]
+fn rule_bonds_between_overlapping_particles_hold() {
+    let particles = vec![Particle::new(Point::new(1000., 1000.), Vector::zero(), ParticleColor::Red); 2];
+    let rule = BondRule { who: ParticleColor::Red, to: ParticleColor::Red, distance: 0.5, stiffness: 1., valence: 1, max_strain: Some(0.5) };
+    let mut world = World::new(particles, ForcesConfig::empty(), PhysicsMode::Emergence).with_bond_rules(vec![rule]);
+    world.tick(&CalculationMethod::SingleThread, 1.);
+    world.tick(&CalculationMethod::SingleThread, 1.);
+
+    assert_eq!(world.bonds().len(), 1);
+    assert!(world.bonds()[0].rest_length > 0.);
+}
@@ -0,0 +1,47 @@
+// The CUDA kernel pulls particles the same way the CPU does, per-pair radii included. Passes without checking
+// anything when there is no CUDA device to run it on.
+#![cfg(feature = "gpu")]
+
+use sim_lib::{CalculationMethod, ForcesConfig, GpuExecutor, PairRadii, Particle, ParticleColor, PhysicsMode, Point, Vector, World};
+
+// Close enough together that every pair sits inside its repel or interaction radius
+fn world() -> World {
+    let particles = (0..64)
+        .map(|i| {
+            let position = Point::new(1800. + 25. * (i % 8) as f32, 900. + 25. * (i / 8) as f32);
+            Particle::new(position, Vector::zero(), ParticleColor::ALL[i % ParticleColor::ALL.len()])
+        })
+        .collect();
+    let forces = ForcesConfig::empty()
+        .with_force(ParticleColor::Red, ParticleColor::Green, 0.8)
+        .with_force(ParticleColor::Green, ParticleColor::Red, -0.5)
+        .with_force(ParticleColor::Blue, ParticleColor::Yellow, 0.3)
+        .with_force(ParticleColor::Yellow, ParticleColor::Yellow, 1.)
+        .with_radii(ParticleColor::Red, ParticleColor::Green, PairRadii { repel: 0.1, interaction: 0.6 })
+        .with_radii(ParticleColor::Green, ParticleColor::Red, PairRadii { repel: 0.5, interaction: 1.5 })
+        .with_radii(ParticleColor::Yellow, ParticleColor::Yellow, PairRadii { repel: 0.2, interaction: 0.4 });
+    World::new(particles, forces, PhysicsMode::Emergence).with_seed(1)
+}
+
+#[test]
+fn matches_the_cpu_with_per_pair_radii() {
+    let executor = match GpuExecutor::try_new() {
+        Ok(executor) => executor,
+        Err(e) => {
+            eprintln!("Skipping, {}", e);
+            return;
+        },
+    };
+    let mut gpu = world();
+    let mut cpu = world();
+    gpu.tick(&CalculationMethod::GPU(executor), 1.);
+    cpu.tick(&CalculationMethod::SingleThread, 1.);
+
+    // Starting from rest, one tick's velocities are the accelerations it was given
+    for (g, c) in gpu.particles().iter().zip(cpu.particles()) {
+        assert!((g.velocity() - c.velocity()).length() < 1e-4, "gpu {:?} cpu {:?}", g.velocity(), c.velocity());
+        assert!((g.position() - c.position()).length() < 1e-3, "gpu {:?} cpu {:?}", g.position(), c.position());
+    }
+    // Or it would pass with the kernel ignoring every force
+    assert!(cpu.particles().iter().any(|p| p.velocity() != Vector::zero()));
+}
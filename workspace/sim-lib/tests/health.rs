@@ -0,0 +1,55 @@
+// What each health policy does with a particle that lost its position
+
+use sim_lib::{CalculationMethod, ForcesConfig, HealthPolicy, Particle, ParticleColor, PhysicsMode, Point, TickStage, Vector, World};
+
+const BROKEN: usize = 1;
+
+fn world(policy: Option<HealthPolicy>) -> World {
+    let particles = (0..3)
+        .map(|i| Particle::new(Point::new(1000. + i as f32 * 200., 1000.), Vector::new(1., 0.), ParticleColor::Red))
+        .collect();
+    let mut world = World::new(particles, ForcesConfig::empty(), PhysicsMode::Emergence).with_health_check(policy);
+    world.particles_mut()[BROKEN] = world.particles()[BROKEN].with_position(Point::new(f32::NAN, 1000.));
+    world.tick(&CalculationMethod::SingleThread, 1.);
+    world
+}
+
+fn assert_reported(world: &World, removed: bool) {
+    let [issue] = world.health_issues() else {
+        panic!("Expected one issue, got {:?}", world.health_issues());
+    };
+    assert_eq!((issue.particle, issue.tick, issue.stage, issue.removed), (BROKEN, 1, TickStage::BeforeTick, removed));
+    assert!(issue.position.x.is_nan());
+}
+
+#[test]
+#[should_panic(expected = "Particle 1 went non-finite on tick 1, stage: before tick")]
+fn panic_policy_panics() {
+    world(Some(HealthPolicy::Panic));
+}
+
+#[test]
+fn repair_policy_stops_and_moves_the_particle() {
+    let world = world(Some(HealthPolicy::Repair));
+    assert_reported(&world, false);
+    assert_eq!(world.particles().len(), 3);
+
+    let repaired = world.particles()[BROKEN];
+    assert!(repaired.position().x.is_finite() && repaired.position().y.is_finite());
+    assert_eq!(repaired.velocity(), Vector::zero());
+}
+
+#[test]
+fn remove_policy_removes_the_particle() {
+    let world = world(Some(HealthPolicy::Remove));
+    assert_reported(&world, true);
+    assert_eq!(world.particles().len(), 2);
+    assert!(world.particles().iter().all(|p| p.position().x.is_finite()));
+}
+
+#[test]
+fn no_policy_checks_nothing() {
+    let world = world(None);
+    assert!(world.health_issues().is_empty());
+    assert_eq!(world.particles().len(), 3);
+}
@@ -0,0 +1,66 @@
+// Obstacles keep particles out however fast they come, and pinned particles never move
+
+use sim_lib::{CalculationMethod, ForcesConfig, Obstacle, Particle, ParticleColor, PhysicsMode, Point, Vector, World};
+
+const CENTER: Point = Point::new(2000., 1000.);
+const SIZE: f32 = 300.;
+const SPEED: f32 = 40.;
+
+// Every particle heads straight for `CENTER`, each tick has to leave them all on the outside
+fn assert_kept_out(obstacle: Obstacle, particles: Vec<Particle>, inside: impl Fn(Point) -> bool) {
+    let mut world = World::new(particles, ForcesConfig::empty(), PhysicsMode::Real).with_obstacles(vec![obstacle]);
+    for tick in 0..60 {
+        world.tick(&CalculationMethod::SingleThread, 1.);
+        if let Some(particle) = world.particles().iter().find(|p| inside(p.position())) {
+            panic!("Particle at {:?} got in on tick {}", particle.position(), tick);
+        }
+    }
+}
+
+fn ring() -> Vec<Particle> {
+    (0..24).map(|i| {
+        let angle = i as f32 * std::f32::consts::TAU / 24.;
+        let direction = Vector::new(angle.cos(), angle.sin());
+        Particle::new(CENTER + direction * 2. * SIZE, direction * -SPEED, ParticleColor::Red)
+    }).collect()
+}
+
+#[test]
+fn walls_block() {
+    let wall = Obstacle::Wall { from: CENTER - Vector::new(0., SIZE), to: CENTER + Vector::new(0., SIZE) };
+    let particles = (0..7)
+        .map(|i| Particle::new(Point::new(CENTER.x - SIZE, CENTER.y - SIZE + 100. * i as f32), Vector::new(SPEED, 0.), ParticleColor::Red))
+        .collect();
+    assert_kept_out(wall, particles, |p| p.x >= CENTER.x);
+}
+
+#[test]
+fn circles_block() {
+    let circle = Obstacle::Circle { center: CENTER, radius: SIZE };
+    assert_kept_out(circle, ring(), |p| (p - CENTER).length() < SIZE);
+}
+
+#[test]
+fn polygons_block() {
+    let square = Obstacle::Polygon { points: [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)].iter()
+        .map(|&(x, y)| CENTER + Vector::new(x, y) * SIZE)
+        .collect() };
+    assert_kept_out(square, ring(), |p| (p.x - CENTER.x).abs() < SIZE && (p.y - CENTER.y).abs() < SIZE);
+}
+
+#[test]
+fn pinned_particles_stay_put() {
+    let forces = ForcesConfig::empty().with_force(ParticleColor::Red, ParticleColor::Red, 1.);
+    let particles: Vec<Particle> = ring().into_iter().enumerate().map(|(i, p)| p.with_pinned(i % 2 == 0)).collect();
+    let mut world = World::new(particles.clone(), forces, PhysicsMode::Emergence);
+    for _ in 0..60 {
+        world.tick(&CalculationMethod::SingleThread, 1.);
+    }
+
+    for (before, after) in particles.iter().zip(world.particles()).filter(|(p, _)| p.is_pinned()) {
+        assert_eq!(after.position(), before.position());
+        assert_eq!(after.velocity(), Vector::zero());
+    }
+    // The others did feel the forces
+    assert!(particles.iter().zip(world.particles()).any(|(before, after)| !before.is_pinned() && after.position() != before.position()));
+}
@@ -0,0 +1,40 @@
+// g(r) against configurations whose answer is known
+
+use sim_lib::{ForcesConfig, Particle, ParticleColor, PhysicsMode, Point, RadialDistribution, Scenario, Vector, World};
+
+#[test]
+fn uniform_gas_is_flat() {
+    let world = Scenario::new(PhysicsMode::Emergence, ForcesConfig::empty())
+        .with_spawn(ParticleColor::Red, 2000)
+        .build_world_seeded(1);
+    let rdf = RadialDistribution::compute(&world, 2., 5).unwrap();
+
+    let curve = rdf.get(ParticleColor::Red, ParticleColor::Red);
+    for (bin, g) in curve.iter().enumerate() {
+        assert!((g - 1.).abs() < 0.15, "g = {} at {}", g, rdf.bin_center(bin));
+    }
+    // Nothing else was spawned
+    assert!(rdf.get(ParticleColor::Red, ParticleColor::Blue).iter().all(|&g| g == 0.));
+}
+
+#[test]
+fn square_lattice_has_four_nearest_neighbors() {
+    // One world unit apart, the next shell is at the square root of 2
+    let particles = (1..40).flat_map(|x| (1..20).map(move |y| (x, y)))
+        .map(|(x, y)| Particle::new(Point::new(x as f32 * 100., y as f32 * 100.), Vector::zero(), ParticleColor::Red))
+        .collect();
+    let world = World::new(particles, ForcesConfig::empty(), PhysicsMode::Emergence);
+    let rdf = RadialDistribution::compute(&world, 1.2, 6).unwrap();
+
+    let stats = rdf.pair_statistics(ParticleColor::Red, ParticleColor::Red);
+    assert!((stats.coordination_number - 4.).abs() < 1e-3, "coordination {}", stats.coordination_number);
+    assert!((stats.peak_distance - 1.).abs() <= rdf.bin_width());
+}
+
+#[test]
+fn rejects_bad_arguments() {
+    let world = World::new(Vec::new(), ForcesConfig::empty(), PhysicsMode::Emergence);
+    assert!(RadialDistribution::compute(&world, 0., 10).is_err());
+    assert!(RadialDistribution::compute(&world, f32::NAN, 10).is_err());
+    assert!(RadialDistribution::compute(&world, 1., 0).is_err());
+}
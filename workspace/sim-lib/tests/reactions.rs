@@ -0,0 +1,22 @@
+// Reaction rules that can't work are left out instead of failing mid-tick
+
+use sim_lib::{CalculationMethod, ForcesConfig, Particle, ParticleColor, PhysicsMode, Point, Reaction, Vector, World};
+
+#[test]
+fn invalid_rules_are_ignored() {
+    let (red, green) = (ParticleColor::Red, ParticleColor::Green);
+    let particles = vec![
+        Particle::new(Point::new(1000., 1000.), Vector::zero(), red),
+        Particle::new(Point::new(1010., 1000.), Vector::zero(), green),
+    ];
+    let mut world = World::new(particles, ForcesConfig::empty(), PhysicsMode::Emergence).with_reactions(vec![
+        Reaction::Contact { who: red, catalyst: green, distance: 1., product: green, probability: f32::NAN },
+        Reaction::Decay { who: red, product: red, ticks: 0 },
+        Reaction::Decay { who: green, product: red, ticks: 0 },
+    ]);
+    world.add_reaction(Reaction::Contact { who: red, catalyst: green, distance: 0., product: green, probability: 1. });
+    world.tick(&CalculationMethod::SingleThread, 1.);
+
+    assert_eq!(world.reactions(), &[Reaction::Decay { who: green, product: red, ticks: 0 }]);
+    assert_eq!(world.reaction_counts(), &[1]);
+}
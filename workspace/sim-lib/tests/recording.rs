@@ -0,0 +1,67 @@
+// What a recording plays back has to match what was recorded, up to the quantization of the format
+
+use std::fs;
+use sim_lib::{CalculationMethod, ForcesConfig, Particle, ParticleColor, PhysicsMode, Recorder, Replay, Scenario};
+
+// Past the 60 frames after which the recorder writes a new keyframe
+const TICKS: usize = 70;
+
+fn record(name: &str) -> (Vec<u8>, Vec<Vec<Particle>>) {
+    let scenario = ParticleColor::ALL.iter()
+        .fold(Scenario::new(PhysicsMode::Real, ForcesConfig::empty()), |s, &color| s.with_spawn(color, 5));
+    let mut world = scenario.build_world_seeded(1);
+    world.particles_mut()[0] = world.particles()[0].with_pinned(true);
+    // Moving, so the delta frames have something to carry
+    world.accelerate_all(3.);
+
+    let path = std::env::temp_dir().join(format!("{}-{}.rec", name, std::process::id()));
+    world.start_recording(Recorder::create(&path, 1).unwrap()).unwrap();
+    let mut history = vec![world.particles().to_vec()];
+    for _ in 0..TICKS {
+        world.tick(&CalculationMethod::SingleThread, 1.);
+        history.push(world.particles().to_vec());
+    }
+    world.stop_recording().unwrap().unwrap().flush().unwrap();
+
+    let data = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    (data, history)
+}
+
+fn assert_close(replayed: &[Particle], recorded: &[Particle]) {
+    assert_eq!(replayed.len(), recorded.len());
+    for (replayed, recorded) in replayed.iter().zip(recorded) {
+        assert_eq!(replayed.color(), recorded.color());
+        assert_eq!(replayed.is_pinned(), recorded.is_pinned());
+        assert!((replayed.position() - recorded.position()).length() < 0.1);
+        assert!((replayed.velocity() - recorded.velocity()).length() < 0.01);
+    }
+}
+
+#[test]
+fn replays_what_was_recorded() {
+    let (data, history) = record("replays-what-was-recorded");
+    let mut replay = Replay::from_bytes(data).unwrap();
+    assert_eq!(replay.len(), TICKS + 1);
+    assert_eq!(replay.physics_mode(), PhysicsMode::Real);
+    assert!(replay.particles()[0].is_pinned());
+
+    // Forward through the second keyframe, then back into the deltas after the first
+    for frame in [TICKS, 10, 11] {
+        replay.seek(frame).unwrap();
+        assert_eq!(replay.position(), frame);
+        assert_eq!(replay.tick(), frame as u64);
+        assert_close(&replay.particles(), &history[frame]);
+    }
+}
+
+#[test]
+fn drops_a_truncated_last_frame() {
+    let (mut data, history) = record("drops-a-truncated-last-frame");
+    data.truncate(data.len() - 3);
+    let mut replay = Replay::from_bytes(data).unwrap();
+    assert_eq!(replay.len(), TICKS);
+
+    replay.seek(TICKS).unwrap();
+    assert_close(&replay.particles(), &history[TICKS - 1]);
+}
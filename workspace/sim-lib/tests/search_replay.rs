@@ -0,0 +1,27 @@
+// A saved winner has to score the same when run again on its own
+
+#![cfg(feature = "threadpool")]
+
+use sim_lib::{CalculationMethod, Fitness, GeneticSearch, Motility, SearchConfig, ThreadPool};
+
+#[test]
+fn winner_scenario_reproduces_score() {
+    let config = SearchConfig {
+        population: 4,
+        generations: 2,
+        elite_count: 1,
+        particles_per_species: 10,
+        ticks: 30,
+        seed: 3,
+        ..SearchConfig::default()
+    };
+    let search = GeneticSearch::new(config).unwrap().with_objective(Motility, 1.);
+    let winner = search.run(&ThreadPool::new(2), |_, _| {})[0];
+
+    let mut world = winner.to_scenario(search.config()).build_world();
+    let initial = world.particles().to_vec();
+    for _ in 0..search.config().ticks {
+        world.tick(&CalculationMethod::SingleThread, 1.);
+    }
+    assert_eq!(Motility.score(&initial, &world), winner.score);
+}
@@ -0,0 +1,26 @@
+// Worlds built from the same scenario and seed have to come out the same, respawns and all
+
+use sim_lib::{CalculationMethod, ForcesConfig, ParticleColor, PhysicsMode, Point, Scenario, Thermostat, World};
+
+fn run(seed: u64) -> Vec<Point> {
+    let scenario = ParticleColor::ALL.iter()
+        .fold(Scenario::new(PhysicsMode::Emergence, ForcesConfig::empty()), |s, &color| s.with_spawn(color, 20))
+        .with_thermostat(Thermostat::at(4.));
+    let mut world: World = scenario.build_world_seeded(seed);
+    // Fast enough that plenty of them leave the world and get respawned
+    world.accelerate_all(500.);
+    for _ in 0..20 {
+        world.tick(&CalculationMethod::SingleThread, 1.);
+    }
+    world.particles().iter().map(|p| p.position()).collect()
+}
+
+#[test]
+fn same_seed_same_world() {
+    assert_eq!(run(7), run(7));
+}
+
+#[test]
+fn different_seed_different_world() {
+    assert_ne!(run(7), run(8));
+}
@@ -0,0 +1,26 @@
+// A sweep run twice with the same seeds has to write the same rows, even where particles leave the world and
+// Emergence respawns them
+
+#![cfg(feature = "threadpool")]
+
+use sim_lib::{ForcesConfig, ParticleColor, PhysicsMode, Scenario, Sweep, SweepAxis, ThreadPool};
+
+fn csv() -> String {
+    let base = ParticleColor::ALL.iter()
+        .fold(Scenario::new(PhysicsMode::Emergence, ForcesConfig::empty()), |s, &color| s.with_spawn(color, 30));
+    // Without friction and this hot the particles cross the edges all the time
+    let sweep = Sweep::new(base, 50)
+        .with_axis(SweepAxis::Friction(vec![0.9, 1.]))
+        .with_axis(SweepAxis::Temperature(vec![100.]))
+        .with_seeds(vec![1, 2]);
+
+    let mut out = Vec::new();
+    sweep.write_csv_header(&mut out).unwrap();
+    sweep.run(&ThreadPool::new(4), |run| sweep.write_csv_row(&mut out, run).unwrap());
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn same_seeds_same_rows() {
+    assert_eq!(csv(), csv());
+}
@@ -0,0 +1,37 @@
+// With Emergence friction off under a thermostat, free particles settle at the target temperature, and bad
+// thermostat parameters are turned away instead of adjusted
+
+use sim_lib::{mean_kinetic_energy, CalculationMethod, ForcesConfig, ParticleColor, PhysicsMode, Scenario, Thermostat};
+
+const TEMPERATURE: f32 = 4.;
+
+#[test]
+fn langevin_reaches_target() {
+    let scenario = ParticleColor::ALL.iter()
+        .fold(Scenario::new(PhysicsMode::Emergence, ForcesConfig::empty()), |s, &color| s.with_spawn(color, 20))
+        .with_thermostat(Thermostat::at(TEMPERATURE));
+    let mut world = scenario.build_world_seeded(1);
+
+    // Well past the 1 / damping ticks it takes to settle, then averaged to smooth out the noise
+    let mut total = 0.;
+    for tick in 0..300 {
+        world.tick(&CalculationMethod::SingleThread, 1.);
+        if tick >= 200 {
+            total += mean_kinetic_energy(world.particles());
+        }
+    }
+    let temperature = total / 100.;
+    assert!((temperature - TEMPERATURE).abs() < 0.1 * TEMPERATURE, "settled at {}", temperature);
+}
+
+#[test]
+fn rejects_bad_parameters() {
+    assert!(Thermostat::berendsen(TEMPERATURE, 0.5).is_err());
+    assert!(Thermostat::berendsen(TEMPERATURE, f32::NAN).is_err());
+    assert!(Thermostat::berendsen(-1., 10.).is_err());
+    assert!(Thermostat::langevin(TEMPERATURE, 1.5).is_err());
+    assert_eq!(Thermostat::berendsen(TEMPERATURE, 10.), Ok(Thermostat::Berendsen { temperature: TEMPERATURE, time_constant: 10. }));
+
+    assert!("berendsen 4 0.5".parse::<Scenario>().is_err());
+    assert_eq!("berendsen 4 10".parse::<Scenario>().unwrap().thermostat, Some(Thermostat::Berendsen { temperature: 4., time_constant: 10. }));
+}
@@ -0,0 +1,85 @@
+// Two equal bodies on an eccentric orbit under the real law, checking that the energy they start with is the
+// energy they end with. The closest approach comes within a softening length, where whole ticks overshoot and
+// fling the bodies apart, so it only holds with substeps.
+
+use sim_lib::{
+    CalculationMethod, ForcesConfig, PairForce, Particle, ParticleColor, PhysicsMode, PhysicsParams, Point, Vector, World,
+    FORCE_SCALAR, WORLD_HEIGHT_FLOAT, WORLD_UNIT_SIZE, WORLD_WIDTH_FLOAT,
+};
+
+const STRENGTH: f32 = 1.;
+const SOFTENING: f32 = 0.1;
+// World units between the bodies at the far end of the orbit
+const SEPARATION: f32 = 4.;
+// Fraction of the circular orbit speed, lower dives closer in
+const SPEED: f32 = 0.15;
+const TICKS: u32 = 2000;
+const TOLERANCE: f32 = 0.01;
+
+#[test]
+fn substeps_conserve_energy() {
+    let (orbits, drift) = orbit(64);
+    assert!(orbits > 0, "The bodies never came back around");
+    assert!(drift < TOLERANCE, "Energy drifted by {}%", drift * 100.);
+}
+
+#[test]
+fn whole_ticks_do_not() {
+    let (_, drift) = orbit(1);
+    assert!(drift > TOLERANCE, "Energy only drifted by {}% without substeps", drift * 100.);
+}
+
+// Completed orbits, and the worst relative energy drift seen at the far end of any of them
+fn orbit(max_substeps: u32) -> (u32, f32) {
+    let color = ParticleColor::ALL[0];
+    let center = Point::new(WORLD_WIDTH_FLOAT / 2., WORLD_HEIGHT_FLOAT / 2.);
+    let offset = Vector::new(SEPARATION * WORLD_UNIT_SIZE / 2., 0.);
+    // Each body circles the common center at half the separation
+    let pull = STRENGTH * SEPARATION / (SEPARATION.powi(2) + SOFTENING.powi(2)).powf(1.5) * FORCE_SCALAR;
+    let velocity = Vector::new(0., f32::sqrt(pull * offset.x) * SPEED);
+
+    let particles = vec![
+        Particle::new(center + offset, velocity, color),
+        Particle::new(center - offset, -velocity, color),
+    ];
+    let mut world = World::new(particles, ForcesConfig::empty().with_force(color, color, STRENGTH), PhysicsMode::Real)
+        .with_force_law(PairForce::Real { softening: SOFTENING })
+        .with_physics_params(PhysicsParams::default().with_max_substeps(max_substeps));
+
+    // Symplectic Euler leaves the velocity half a step behind the position, which shows up as an energy error
+    // proportional to how fast the bodies are falling. At the far end of the orbit they aren't, so that is where
+    // the energy is compared.
+    let initial = energy(&world);
+    let (mut worst, mut orbits) = (0f32, 0);
+    let mut distances = [separation(&world); 2];
+    for _ in 0..TICKS {
+        world.tick(&CalculationMethod::SingleThread, 1.);
+        let distance = separation(&world);
+        if distances[1] > distances[0] && distances[1] >= distance {
+            worst = worst.max(f32::abs((energy(&world) - initial) / initial));
+            orbits += 1;
+        }
+        distances = [distances[1], distance];
+    }
+
+    (orbits, worst)
+}
+
+// Kinetic plus the softened potential, in pixels squared per tick squared. The pull is in world units while the
+// velocities are in pixels, hence the world unit size in the potential.
+fn energy(world: &World) -> f32 {
+    let [a, b] = world.particles() else {
+        unreachable!("The orbit has two bodies");
+    };
+    let distance = separation(world);
+    let kinetic = 0.5 * (a.velocity().square_length() + b.velocity().square_length());
+    let potential = -STRENGTH * FORCE_SCALAR * WORLD_UNIT_SIZE / (distance.powi(2) + SOFTENING.powi(2)).sqrt();
+    kinetic + potential
+}
+
+fn separation(world: &World) -> f32 {
+    let [a, b] = world.particles() else {
+        unreachable!("The orbit has two bodies");
+    };
+    a.position().distance_to(b.position()) / WORLD_UNIT_SIZE
+}
@@ -0,0 +1,22 @@
+# Build with `maturin develop` from workspace/sim-py, then run with python.
+import numpy as np
+import sim_py
+
+forces = sim_py.ForcesConfig.random(-1.0, 1.0, seed=7)
+world = sim_py.World.random(forces, sim_py.PhysicsMode.Emergence, 300, seed=7)
+method = sim_py.CalculationMethod.thread_pool()
+
+# Views into the simulation, they follow every tick without copying
+positions = world.positions
+velocities = world.velocities
+colors = world.colors
+
+for _ in range(10):
+    world.tick(50, method)
+    speeds = np.linalg.norm(velocities, axis=1)
+    per_color = [speeds[colors == i].mean() for i in range(len(sim_py.COLORS))]
+    print(world.ticks, " ".join(f"{name}={speed:.3f}" for name, speed in zip(sim_py.COLORS, per_color)))
+
+hist, _, _ = np.histogram2d(positions[:, 0], positions[:, 1], bins=8,
+                            range=[[0, sim_py.WORLD_WIDTH], [0, sim_py.WORLD_HEIGHT]])
+print(hist.astype(int))
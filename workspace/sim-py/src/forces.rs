@@ -0,0 +1,66 @@
+use ndarray::Array2;
+use numpy::{IntoPyArray, PyArray2, PyReadonlyArray2, PyUntypedArrayMethods};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rand::{rngs::StdRng, SeedableRng};
+use sim_lib::{ForcesConfig, ParticleColor};
+use crate::parse_color;
+
+#[pyclass(name = "ForcesConfig", from_py_object)]
+#[derive(Copy, Clone)]
+pub struct PyForcesConfig(pub ForcesConfig);
+
+#[pymethods]
+impl PyForcesConfig {
+    #[new]
+    fn new() -> Self {
+        PyForcesConfig(ForcesConfig::empty())
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (min_force, max_force, seed = None))]
+    fn random(min_force: f32, max_force: f32, seed: Option<u64>) -> PyResult<Self> {
+        if min_force.is_nan() || min_force >= f32::abs(max_force) {
+            return Err(PyValueError::new_err("min_force must be lower than max_force"));
+        }
+        Ok(PyForcesConfig(match seed {
+            Some(seed) => ForcesConfig::random_with(&mut StdRng::seed_from_u64(seed), min_force, max_force),
+            None => ForcesConfig::random(min_force, max_force),
+        }))
+    }
+
+    // Rows are the affected color, columns the color it reacts to, both in COLORS order
+    #[staticmethod]
+    fn from_numpy(matrix: PyReadonlyArray2<f32>) -> PyResult<Self> {
+        let len = ParticleColor::matrix_len();
+        if matrix.shape() != [len, len] {
+            return Err(PyValueError::new_err(format!("Expected a {0}x{0} matrix, got {1:?}", len, matrix.shape())));
+        }
+        let matrix = matrix.as_array();
+        Ok(PyForcesConfig(matrix.indexed_iter().fold(ForcesConfig::empty(), |c, ((who, to), &force)| {
+            c.with_force_unchecked(who, to, force)
+        })))
+    }
+
+    #[pyo3(name = "to_numpy")]
+    fn numpy_matrix<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f32>> {
+        let len = ParticleColor::matrix_len();
+        Array2::from_shape_fn((len, len), |(who, to)| self.0.get_unchecked(who, to)).into_pyarray(py)
+    }
+
+    fn get(&self, who: &str, to: &str) -> PyResult<f32> {
+        Ok(self.0.get(parse_color(who)?, parse_color(to)?))
+    }
+
+    fn set(&mut self, who: &str, to: &str, force: f32) -> PyResult<()> {
+        self.0 = self.0.with_force(parse_color(who)?, parse_color(to)?, force);
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        let rows: Vec<String> = ParticleColor::ALL.iter().map(|&who| {
+            let row: Vec<String> = ParticleColor::ALL.iter().map(|&to| self.0.get(who, to).to_string()).collect();
+            format!("[{}]", row.join(", "))
+        }).collect();
+        format!("ForcesConfig([{}])", rows.join(", "))
+    }
+}
@@ -0,0 +1,100 @@
+mod forces;
+mod world;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use sim_lib::{
+    CalculationMethod, ParticleColor, PhysicsMode, ThreadPool,
+    WORLD_HEIGHT_FLOAT, WORLD_WIDTH_FLOAT,
+};
+use forces::PyForcesConfig;
+use world::PyWorld;
+
+#[pyclass(name = "PhysicsMode", eq, eq_int, from_py_object)]
+#[derive(Copy, Clone, PartialEq)]
+pub enum PyPhysicsMode {
+    Real,
+    Emergence,
+}
+
+// Only the CPU methods are exposed, the GPU executor holds a CUDA context that can't be shared with Python threads
+#[pyclass(name = "CalculationMethod", frozen)]
+pub struct PyCalculationMethod {
+    thread_pool: Option<ThreadPool>,
+}
+
+impl From<PyPhysicsMode> for PhysicsMode {
+    fn from(mode: PyPhysicsMode) -> Self {
+        match mode {
+            PyPhysicsMode::Real => PhysicsMode::Real,
+            PyPhysicsMode::Emergence => PhysicsMode::Emergence,
+        }
+    }
+}
+
+impl From<PhysicsMode> for PyPhysicsMode {
+    fn from(mode: PhysicsMode) -> Self {
+        match mode {
+            PhysicsMode::Real => PyPhysicsMode::Real,
+            PhysicsMode::Emergence => PyPhysicsMode::Emergence,
+        }
+    }
+}
+
+#[pymethods]
+impl PyCalculationMethod {
+    #[staticmethod]
+    fn single_thread() -> Self {
+        PyCalculationMethod { thread_pool: None }
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (threads = None))]
+    fn thread_pool(threads: Option<usize>) -> PyResult<Self> {
+        match threads.unwrap_or_else(num_cpus::get) {
+            0 => Err(PyValueError::new_err("A thread pool needs at least one thread")),
+            threads => Ok(PyCalculationMethod { thread_pool: Some(ThreadPool::new(threads)) }),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.thread_pool {
+            Some(pool) => format!("CalculationMethod.thread_pool({})", pool.max_count()),
+            None => "CalculationMethod.single_thread()".to_string(),
+        }
+    }
+}
+
+impl PyCalculationMethod {
+    // Pools are cheap to clone, the clone shares the workers of the original
+    pub(crate) fn thread_pool_handle(&self) -> Option<ThreadPool> {
+        self.thread_pool.clone()
+    }
+}
+
+pub(crate) fn calculation_method(thread_pool: Option<ThreadPool>) -> CalculationMethod {
+    match thread_pool {
+        Some(pool) => CalculationMethod::ThreadPool(pool),
+        None => CalculationMethod::SingleThread,
+    }
+}
+
+pub(crate) fn parse_color(name: &str) -> PyResult<ParticleColor> {
+    ParticleColor::from_name(name).ok_or_else(|| PyValueError::new_err(format!("Unknown color '{}'", name)))
+}
+
+pub(crate) fn color_from_index(index: usize) -> PyResult<ParticleColor> {
+    ParticleColor::ALL.get(index).copied().ok_or_else(|| PyValueError::new_err(format!("Unknown color index {}", index)))
+}
+
+#[pymodule]
+fn sim_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyWorld>()?;
+    m.add_class::<PyForcesConfig>()?;
+    m.add_class::<PyPhysicsMode>()?;
+    m.add_class::<PyCalculationMethod>()?;
+    // Color names in index order, the same order used by the colors array and the forces matrix
+    m.add("COLORS", ParticleColor::ALL.iter().map(|c| c.name()).collect::<Vec<_>>())?;
+    m.add("WORLD_WIDTH", WORLD_WIDTH_FLOAT)?;
+    m.add("WORLD_HEIGHT", WORLD_HEIGHT_FLOAT)?;
+    Ok(())
+}
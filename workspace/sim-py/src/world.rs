@@ -0,0 +1,233 @@
+use std::mem::size_of;
+use ndarray::{ArrayView2, ShapeBuilder};
+use numpy::{AllowTypeChange, PyArray1, PyArray2, PyArrayLike2};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use sim_lib::{
+    HealthPolicy, Impulse, Particle, ParticleColor, PhysicsParams, Point, Scenario, Vector, World,
+};
+use crate::{
+    calculation_method, color_from_index, forces::PyForcesConfig, PyCalculationMethod, PyPhysicsMode,
+};
+
+// The particle count is fixed for the lifetime of a Python world, the storage never reallocates
+// and that is what keeps the positions/velocities views valid for as long as they are alive. That
+// rules out the remove health policy.
+#[pyclass(name = "World")]
+pub struct PyWorld {
+    world: World,
+}
+
+#[pymethods]
+impl PyWorld {
+    #[new]
+    #[pyo3(signature = (forces, physics_mode, positions, colors, velocities = None))]
+    fn new(
+        forces: PyForcesConfig,
+        physics_mode: PyPhysicsMode,
+        positions: PyArrayLike2<f32, AllowTypeChange>,
+        colors: Vec<usize>,
+        velocities: Option<PyArrayLike2<f32, AllowTypeChange>>,
+    ) -> PyResult<Self> {
+        let positions = positions.as_array();
+        let velocities = velocities.as_ref().map(|v| v.as_array());
+        let len = colors.len();
+
+        if positions.dim() != (len, 2) {
+            return Err(PyValueError::new_err(format!("Expected positions of shape ({}, 2), got {:?}", len, positions.shape())));
+        }
+        if let Some(velocities) = velocities.as_ref().filter(|v| v.dim() != (len, 2)) {
+            return Err(PyValueError::new_err(format!("Expected velocities of shape ({}, 2), got {:?}", len, velocities.shape())));
+        }
+
+        let particles = colors.into_iter().enumerate().map(|(i, color)| {
+            let velocity = velocities.as_ref().map_or(Vector::zero(), |v| Vector::new(v[[i, 0]], v[[i, 1]]));
+            Ok(Particle::new(Point::new(positions[[i, 0]], positions[[i, 1]]), velocity, color_from_index(color)?))
+        }).collect::<PyResult<Vec<_>>>()?;
+
+        Ok(PyWorld { world: World::new(particles, forces.0, physics_mode.into()) })
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (forces, physics_mode, particles_per_species, seed = None))]
+    fn random(forces: PyForcesConfig, physics_mode: PyPhysicsMode, particles_per_species: usize, seed: Option<u64>) -> Self {
+        let scenario = ParticleColor::ALL.iter().fold(Scenario::new(physics_mode.into(), forces.0), |s, &color| {
+            s.with_spawn(color, particles_per_species)
+        });
+        let world = match seed {
+            Some(seed) => scenario.build_world_seeded(seed),
+            None => scenario.build_world(),
+        };
+        PyWorld { world }
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (path, seed = None))]
+    fn load_scenario(path: &str, seed: Option<u64>) -> PyResult<Self> {
+        let scenario = Scenario::load(path).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        if scenario.health == Some(HealthPolicy::Remove) {
+            return Err(PyValueError::new_err("The remove health policy changes the particle count, use panic or repair"));
+        }
+        let world = match seed {
+            Some(seed) => scenario.build_world_seeded(seed),
+            None => scenario.build_world(),
+        };
+        Ok(PyWorld { world })
+    }
+
+    fn save_scenario(&self, path: &str) -> PyResult<()> {
+        Ok(Scenario::from_world(&self.world).save(path)?)
+    }
+
+    // Runs `n` ticks of `dt` with the GIL released and returns the substeps they took. Defaults to the single
+    // threaded method. Other Python threads keep running meanwhile, see `positions` for what they must not do.
+    #[pyo3(signature = (n = 1, method = None, dt = 1.))]
+    fn tick(&mut self, py: Python<'_>, n: u64, method: Option<&PyCalculationMethod>, dt: f32) -> PyResult<u64> {
+        if !dt.is_finite() || dt <= 0. {
+            return Err(PyValueError::new_err(format!("dt has to be finite and positive, got {}", dt)));
+        }
+        let thread_pool = method.and_then(PyCalculationMethod::thread_pool_handle);
+        let world = &mut self.world;
+        py.detach(move || {
+            let calculation_method = calculation_method(thread_pool);
+            Ok((0..n).map(|_| world.tick(&calculation_method, dt) as u64).sum())
+        })
+    }
+
+    // Writable (N, 2) float32 views straight into the particle storage. `tick` runs without the GIL and writes the
+    // same memory, so another Python thread must not read or write these views while a tick is running.
+    #[getter]
+    fn positions(slf: Bound<'_, Self>) -> Bound<'_, PyArray2<f32>> {
+        Self::particle_field_view(slf, Particle::POSITION_OFFSET)
+    }
+
+    #[getter]
+    fn velocities(slf: Bound<'_, Self>) -> Bound<'_, PyArray2<f32>> {
+        Self::particle_field_view(slf, Particle::VELOCITY_OFFSET)
+    }
+
+    // Color indices into COLORS. A copy, colors can't be changed from Python.
+    #[getter]
+    fn colors<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<usize>> {
+        PyArray1::from_iter(py, self.world.particles().iter().map(|p| p.color() as usize))
+    }
+
+    #[getter]
+    fn ticks(&self) -> u64 {
+        self.world.ticks()
+    }
+
+    #[getter]
+    fn physics_mode(&self) -> PyPhysicsMode {
+        self.world.physics_mode().into()
+    }
+
+    #[getter]
+    fn get_forces(&self) -> PyForcesConfig {
+        PyForcesConfig(self.world.get_forces_config())
+    }
+
+    #[setter]
+    fn set_forces(&mut self, forces: PyForcesConfig) {
+        self.world.set_forces_config(forces.0);
+    }
+
+    #[getter]
+    fn get_friction(&self) -> f32 {
+        self.world.get_physics_params().friction
+    }
+
+    #[setter]
+    fn set_friction(&mut self, friction: f32) {
+        self.update_params(|p| p.with_friction(friction));
+    }
+
+    #[getter]
+    fn get_repel_radius(&self) -> f32 {
+        self.world.get_physics_params().repel_radius
+    }
+
+    #[setter]
+    fn set_repel_radius(&mut self, repel_radius: f32) {
+        self.update_params(|p| p.with_repel_radius(repel_radius));
+    }
+
+    #[getter]
+    fn get_max_substeps(&self) -> u32 {
+        self.world.get_physics_params().max_substeps
+    }
+
+    #[setter]
+    fn set_max_substeps(&mut self, max_substeps: u32) {
+        self.update_params(|p| p.with_max_substeps(max_substeps));
+    }
+
+    #[getter]
+    fn get_max_displacement(&self) -> f32 {
+        self.world.get_physics_params().max_displacement
+    }
+
+    #[setter]
+    fn set_max_displacement(&mut self, max_displacement: f32) {
+        self.update_params(|p| p.with_max_displacement(max_displacement));
+    }
+
+    // Impulses change velocities right away, `color` limits them to one species. Random directions come from the
+    // world's own generator, so seeded worlds get the same ones.
+    #[pyo3(signature = (amount, color = None))]
+    fn boost(&mut self, amount: f32, color: Option<usize>) -> PyResult<()> {
+        self.apply_impulse(Impulse::Boost { amount }, color)
+    }
+
+    #[pyo3(signature = (strength, color = None))]
+    fn kick(&mut self, strength: f32, color: Option<usize>) -> PyResult<()> {
+        self.apply_impulse(Impulse::Kick { strength }, color)
+    }
+
+    #[pyo3(signature = (x, y, strength, radius, color = None))]
+    fn explode(&mut self, x: f32, y: f32, strength: f32, radius: f32, color: Option<usize>) -> PyResult<()> {
+        self.apply_impulse(Impulse::Explosion { center: Point::new(x, y), strength, radius }, color)
+    }
+
+    fn __len__(&self) -> usize {
+        self.world.particles().len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("World({} particles, {} physics, {} ticks)", self.world.particles().len(), self.world.physics_mode().name(), self.world.ticks())
+    }
+}
+
+impl PyWorld {
+    fn apply_impulse(&mut self, impulse: Impulse, color: Option<usize>) -> PyResult<()> {
+        match color {
+            Some(color) => self.world.apply_impulse_to(impulse, color_from_index(color)?),
+            None => self.world.apply_impulse(impulse),
+        }
+        Ok(())
+    }
+
+    fn update_params<F: FnOnce(PhysicsParams) -> PhysicsParams>(&mut self, f: F) {
+        let params = f(self.world.get_physics_params());
+        self.world.set_physics_params(params);
+    }
+
+    fn particle_field_view(slf: Bound<'_, Self>, offset: usize) -> Bound<'_, PyArray2<f32>> {
+        let stride = size_of::<Particle>() / size_of::<f32>();
+        let view = {
+            let mut this = slf.borrow_mut();
+            let particles = this.world.particles_mut();
+            if particles.is_empty() {
+                return PyArray2::zeros(slf.py(), (0, 2), false);
+            }
+            // Safety: Particle is repr(C) and `offset` points at a pair of f32 inside it, so every
+            // row starts `stride` floats after the previous one and stays inside the allocation.
+            unsafe {
+                let first = (particles.as_mut_ptr() as *mut u8).add(offset) as *mut f32;
+                ArrayView2::from_shape_ptr((particles.len(), 2).strides((stride, 1)), first)
+            }
+        };
+        // Safety: the array keeps the world object alive through its base, and the storage
+        // it points into is never reallocated (see PyWorld).
+        unsafe { PyArray2::borrow_from_array(&view, slf.into_any()) }
+    }
+}
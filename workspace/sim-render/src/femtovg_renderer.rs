@@ -0,0 +1,80 @@
+use std::convert::Infallible;
+use femtovg::{Canvas, Paint, Path};
+use crate::{Color, Frame, Line, Renderer};
+
+const SQUARE_PARTICLE_MAX_RADIUS: f32 = 1.5;
+const MIN_SQUARE_PARTICLE_RADIUS: f32 = 0.5;
+
+pub struct FemtovgRenderer<'a, R: femtovg::Renderer> {
+    canvas: &'a mut Canvas<R>,
+}
+
+impl<'a, R: femtovg::Renderer> FemtovgRenderer<'a, R> {
+    pub fn new(canvas: &'a mut Canvas<R>) -> Self {
+        FemtovgRenderer { canvas }
+    }
+}
+
+impl<R: femtovg::Renderer> Renderer for FemtovgRenderer<'_, R> {
+    type Error = Infallible;
+
+    fn render(&mut self, frame: &Frame) -> Result<(), Self::Error> {
+        self.canvas.clear_rect(0, 0, frame.width as u32, frame.height as u32, frame.background.into());
+        self.stroke_lines(&frame.geometry);
+        self.stroke_lines(&frame.trails);
+
+        // One path and fill call per color (two when sizes are mixed), per-particle fills used to dominate the frame time
+        for batch in frame.batches() {
+            let mut squares = Path::new();
+            let mut circles = Path::new();
+            let (mut has_squares, mut has_circles) = (false, false);
+
+            for particle in batch.particles {
+                let (x, y, radius) = (particle.position.x, particle.position.y, particle.style.radius);
+                // Below a couple of pixels a square looks the same and tessellates into far fewer vertices.
+                // They are kept at least a pixel wide since they are drawn without anti-aliasing.
+                if radius < SQUARE_PARTICLE_MAX_RADIUS {
+                    let radius = f32::max(radius, MIN_SQUARE_PARTICLE_RADIUS);
+                    squares.rect(x - radius, y - radius, radius * 2., radius * 2.);
+                    has_squares = true;
+                } else {
+                    circles.circle(x, y, radius);
+                    has_circles = true;
+                }
+            }
+
+            // Anti-aliasing fringes cost as much as the shapes themselves and do nothing for pixel sized squares
+            if has_squares {
+                self.canvas.fill_path(&squares, &Paint::color(batch.color.into()).with_anti_alias(false));
+            }
+            if has_circles {
+                self.canvas.fill_path(&circles, &Paint::color(batch.color.into()));
+            }
+        }
+
+        self.stroke_lines(&frame.arrows);
+        Ok(())
+    }
+}
+
+impl<R: femtovg::Renderer> FemtovgRenderer<'_, R> {
+    fn stroke_lines(&mut self, lines: &[Line]) {
+        for batch in Frame::line_batches(lines) {
+            let mut path = Path::new();
+            for line in batch.lines {
+                path.move_to(line.from.x, line.from.y);
+                path.line_to(line.to.x, line.to.y);
+            }
+
+            let mut color: femtovg::Color = batch.color.into();
+            color.set_alphaf(batch.opacity);
+            self.canvas.stroke_path(&path, &Paint::color(color).with_line_width(batch.width));
+        }
+    }
+}
+
+impl From<Color> for femtovg::Color {
+    fn from(color: Color) -> Self {
+        femtovg::Color::rgb(color.r, color.g, color.b)
+    }
+}
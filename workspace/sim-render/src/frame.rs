@@ -0,0 +1,315 @@
+use std::{collections::HashMap, f32::consts::TAU};
+use sim_lib::{
+    Bond, Obstacle, Particle, ParticleColor, Point, Vector, World,
+    WORLD_HEIGHT_FLOAT, WORLD_UNIT_SIZE, WORLD_WIDTH_FLOAT,
+};
+use crate::{FrameStyle, Trails};
+
+pub const BASE_PARTICLE_RADIUS: f32 = 3.0;
+// Arrows show where a particle would be after this many ticks at its current velocity
+const ARROW_TICKS_AHEAD: f32 = 10.;
+const ARROW_HEAD_LENGTH: f32 = 4.;
+const ARROW_HEAD_ANGLE: f32 = 2.6;
+const MIN_ARROW_LENGTH: f32 = 1.;
+const LINE_WIDTH: f32 = 1.;
+const TRAIL_MAX_OPACITY: f32 = 0.6;
+// Emergence moves particles that leave the world to a random position, longer steps are those jumps
+const TRAIL_MAX_SEGMENT_LENGTH: f32 = WORLD_UNIT_SIZE;
+const BOUNDARY_COLOR: Color = Color::rgb(90, 90, 90);
+const OBSTACLE_COLOR: Color = Color::rgb(200, 200, 200);
+const OBSTACLE_LINE_WIDTH: f32 = 2.;
+const BOND_COLOR: Color = Color::rgb(220, 220, 220);
+const BOND_OPACITY: f32 = 0.5;
+// Circles are drawn as regular polygons so every backend only needs to know about lines
+const CIRCLE_SEGMENTS: usize = 48;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParticleStyle {
+    pub color: Color,
+    pub radius: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Line {
+    pub from: Point,
+    pub to: Point,
+    pub color: Color,
+    pub opacity: f32,
+    pub width: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DrawParticle {
+    pub position: Point,
+    pub style: ParticleStyle,
+}
+
+// Maps world coordinates to screen pixels. `camera` is the world position drawn at the top left corner.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Viewport {
+    pub camera: Point,
+    pub scale: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+// Everything a backend needs to draw one picture of the world, in drawing order: geometry (world edges,
+// obstacles and bonds), trails, particles, arrows
+pub struct Frame {
+    pub width: f32,
+    pub height: f32,
+    pub background: Color,
+    pub geometry: Vec<Line>,
+    pub trails: Vec<Line>,
+    pub particles: Vec<DrawParticle>,
+    pub arrows: Vec<Line>,
+}
+
+// Particles sharing a fill color, so backends can draw each batch with a single fill call
+pub struct Batch<'a> {
+    pub color: Color,
+    pub particles: Vec<&'a DrawParticle>,
+}
+
+pub struct LineBatch<'a> {
+    pub color: Color,
+    pub opacity: f32,
+    pub width: f32,
+    pub lines: Vec<&'a Line>,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b }
+    }
+}
+
+impl From<ParticleColor> for Color {
+    fn from(color: ParticleColor) -> Self {
+        match color {
+            ParticleColor::Blue => Color::rgb(0, 0, 255),
+            ParticleColor::Green => Color::rgb(0, 255, 0),
+            ParticleColor::Red => Color::rgb(255, 0, 0),
+            ParticleColor::Yellow => Color::rgb(252, 186, 3),
+        }
+    }
+}
+
+impl Viewport {
+    pub fn new(camera: Point, scale: f32, width: f32, height: f32) -> Self {
+        Viewport { camera, scale, width, height }
+    }
+
+    // The whole world centered in a `width` x `height` surface
+    pub fn fit_world(width: f32, height: f32) -> Self {
+        let scale = f32::min(width / WORLD_WIDTH_FLOAT, height / WORLD_HEIGHT_FLOAT);
+        let camera = Point::new(
+            -(width / scale - WORLD_WIDTH_FLOAT) / 2.,
+            -(height / scale - WORLD_HEIGHT_FLOAT) / 2.,
+        );
+        Viewport { camera, scale, width, height }
+    }
+
+    pub fn world_to_screen(&self, position: Point) -> Point {
+        ((position - self.camera) * self.scale).to_point()
+    }
+
+    pub fn screen_to_world(&self, position: Point) -> Point {
+        self.camera + position.to_vector() / self.scale
+    }
+
+    fn is_visible(&self, screen_position: Point, margin: f32) -> bool {
+        screen_position.x >= -margin && screen_position.x <= self.width + margin
+            && screen_position.y >= -margin && screen_position.y <= self.height + margin
+    }
+}
+
+impl Frame {
+    pub fn from_world(world: &World, viewport: &Viewport) -> Self {
+        Self::styled(world.particles(), viewport, &FrameStyle::default().with_obstacles(world.obstacles()).with_bonds(world.bonds()))
+    }
+
+    pub fn from_particles(particles: &[Particle], viewport: &Viewport) -> Self {
+        Self::styled(particles, viewport, &FrameStyle::default())
+    }
+
+    pub fn styled(particles: &[Particle], viewport: &Viewport, style: &FrameStyle) -> Self {
+        let colors = style.color_mode.colors(particles);
+        let radius = BASE_PARTICLE_RADIUS * viewport.scale;
+        let mut frame = Frame {
+            width: viewport.width,
+            height: viewport.height,
+            background: Color::BLACK,
+            geometry: geometry_lines(style, viewport),
+            trails: style.trails.map_or_else(Vec::new, |trails| trail_lines(trails, &colors, viewport)),
+            particles: Vec::new(),
+            arrows: Vec::new(),
+        };
+
+        frame.geometry.extend(bond_lines(particles, style.bonds, viewport));
+
+        for (particle, &color) in particles.iter().zip(colors.iter()) {
+            let position = viewport.world_to_screen(particle.position());
+            if !viewport.is_visible(position, radius) {
+                continue;
+            }
+
+            frame.particles.push(DrawParticle { position, style: ParticleStyle { color, radius } });
+            if style.arrows {
+                frame.arrows.extend(arrow_lines(position, particle.velocity() * ARROW_TICKS_AHEAD * viewport.scale, color));
+            }
+        }
+        frame
+    }
+
+    // Batches come in the order their color first appears in the frame
+    pub fn batches(&self) -> Vec<Batch<'_>> {
+        let mut batches: Vec<Batch> = Vec::new();
+        let mut index_by_color = HashMap::new();
+
+        for particle in self.particles.iter() {
+            let index = *index_by_color.entry(particle.style.color).or_insert_with(|| {
+                batches.push(Batch { color: particle.style.color, particles: Vec::new() });
+                batches.len() - 1
+            });
+            batches[index].particles.push(particle);
+        }
+        batches
+    }
+
+    // Same idea as `batches`, lines sharing a color, opacity and width can go in a single stroke
+    pub fn line_batches(lines: &[Line]) -> Vec<LineBatch<'_>> {
+        let mut batches: Vec<LineBatch> = Vec::new();
+        let mut index_by_key = HashMap::new();
+
+        for line in lines.iter() {
+            let key = (line.color, line.opacity.to_bits(), line.width.to_bits());
+            let index = *index_by_key.entry(key).or_insert_with(|| {
+                batches.push(LineBatch { color: line.color, opacity: line.opacity, width: line.width, lines: Vec::new() });
+                batches.len() - 1
+            });
+            batches[index].lines.push(line);
+        }
+        batches
+    }
+}
+
+// World edges and obstacle outlines, clipped to the screen by the backends like any other line
+fn geometry_lines(style: &FrameStyle, viewport: &Viewport) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut add_segments = |segments: &[(Point, Point)], color, width| {
+        lines.extend(segments.iter().map(|&(from, to)| Line {
+            from: viewport.world_to_screen(from),
+            to: viewport.world_to_screen(to),
+            color,
+            opacity: 1.,
+            width,
+        }));
+    };
+
+    if style.boundary {
+        let corners = [
+            Point::new(0., 0.),
+            Point::new(WORLD_WIDTH_FLOAT, 0.),
+            Point::new(WORLD_WIDTH_FLOAT, WORLD_HEIGHT_FLOAT),
+            Point::new(0., WORLD_HEIGHT_FLOAT),
+        ];
+        add_segments(&closed_outline(&corners), BOUNDARY_COLOR, LINE_WIDTH);
+    }
+
+    for obstacle in style.obstacles.iter() {
+        let segments = match obstacle {
+            Obstacle::Circle { center, radius } => {
+                let points: Vec<Point> = (0..CIRCLE_SEGMENTS)
+                    .map(|i| {
+                        let (sin, cos) = (i as f32 * TAU / CIRCLE_SEGMENTS as f32).sin_cos();
+                        *center + Vector::new(cos, sin) * *radius
+                    })
+                    .collect();
+                closed_outline(&points)
+            },
+            _ => obstacle.edges(),
+        };
+        add_segments(&segments, OBSTACLE_COLOR, OBSTACLE_LINE_WIDTH);
+    }
+    lines
+}
+
+// Bonds pointing past the particles (a replay drawn with some world's bonds) are skipped
+fn bond_lines(particles: &[Particle], bonds: &[Bond], viewport: &Viewport) -> Vec<Line> {
+    bonds.iter()
+        .filter(|bond| bond.a < particles.len() && bond.b < particles.len())
+        .map(|bond| Line {
+            from: viewport.world_to_screen(particles[bond.a].position()),
+            to: viewport.world_to_screen(particles[bond.b].position()),
+            color: BOND_COLOR,
+            opacity: BOND_OPACITY,
+            width: LINE_WIDTH,
+        })
+        .collect()
+}
+
+fn closed_outline(points: &[Point]) -> Vec<(Point, Point)> {
+    (0..points.len()).map(|i| (points[i], points[(i + 1) % points.len()])).collect()
+}
+
+// Older segments fade out, each trail takes the color of its particle
+fn trail_lines(trails: &Trails, colors: &[Color], viewport: &Viewport) -> Vec<Line> {
+    let history = trails.history();
+    // Not recorded since the particle count changed, the indices no longer line up
+    if history.back().is_some_and(|positions| positions.len() != colors.len()) {
+        return Vec::new();
+    }
+
+    let segments = history.len().saturating_sub(1);
+    let mut lines = Vec::new();
+
+    for age in 0..segments {
+        let (older, newer) = (&history[age], &history[age + 1]);
+        let opacity = TRAIL_MAX_OPACITY * (age + 1) as f32 / segments as f32;
+
+        for (i, (&from, &to)) in older.iter().zip(newer.iter()).enumerate() {
+            if (to - from).length() > TRAIL_MAX_SEGMENT_LENGTH {
+                continue;
+            }
+            let (from, to) = (viewport.world_to_screen(from), viewport.world_to_screen(to));
+            if !viewport.is_visible(from, 0.) && !viewport.is_visible(to, 0.) {
+                continue;
+            }
+            lines.push(Line { from, to, color: colors[i], opacity, width: LINE_WIDTH });
+        }
+    }
+    lines
+}
+
+fn arrow_lines(from: Point, offset: Vector, color: Color) -> Vec<Line> {
+    let length = offset.length();
+    if length.is_nan() || length < MIN_ARROW_LENGTH {
+        return Vec::new();
+    }
+
+    let to = from + offset;
+    let head_length = f32::min(ARROW_HEAD_LENGTH, length / 2.);
+    let direction = offset / length;
+    let line = |from, to| Line { from, to, color, opacity: 1., width: LINE_WIDTH };
+
+    vec![
+        line(from, to),
+        line(to, to + rotate(direction, ARROW_HEAD_ANGLE) * head_length),
+        line(to, to + rotate(direction, -ARROW_HEAD_ANGLE) * head_length),
+    ]
+}
+
+fn rotate(v: Vector, angle: f32) -> Vector {
+    let (sin, cos) = angle.sin_cos();
+    Vector::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+}
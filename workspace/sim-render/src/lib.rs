@@ -0,0 +1,23 @@
+mod frame;
+mod style;
+mod raster;
+mod svg;
+mod terminal;
+#[cfg(feature = "femtovg")]
+mod femtovg_renderer;
+
+pub use frame::{Batch, Color, DrawParticle, Frame, Line, LineBatch, ParticleStyle, Viewport, BASE_PARTICLE_RADIUS};
+pub use style::{ColorMode, FrameStyle, Trails};
+pub use raster::RasterRenderer;
+pub use svg::SvgRenderer;
+pub use terminal::TerminalRenderer;
+#[cfg(feature = "femtovg")]
+pub use femtovg_renderer::FemtovgRenderer;
+
+// A backend that knows how to put a frame on some surface. Frames are already in screen space,
+// so backends don't need to know anything about the world or the camera.
+pub trait Renderer {
+    type Error;
+
+    fn render(&mut self, frame: &Frame) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,109 @@
+use std::{
+    convert::Infallible,
+    io::{self, Write},
+};
+use crate::{Color, Frame, Line, Renderer};
+
+// Software renderer into an RGB pixel buffer, sized to whatever frame it is given
+#[derive(Default)]
+pub struct RasterRenderer {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl RasterRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // Row major, `width * height` long
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    // Binary PPM (P6), readable by most image tools without pulling in an encoder
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        let bytes: Vec<u8> = self.pixels.iter().flat_map(|c| [c.r, c.g, c.b]).collect();
+        writer.write_all(&bytes)?;
+        writer.flush()
+    }
+
+    // One pixel wide whatever the line width, blended over what is already there
+    fn draw_line(&mut self, line: &Line) {
+        let delta = line.to - line.from;
+        let steps = f32::max(delta.x.abs(), delta.y.abs()).ceil();
+        if !steps.is_finite() {
+            return;
+        }
+
+        for step in 0..=steps as usize {
+            let point = line.from + delta * (step as f32 / f32::max(steps, 1.));
+            if point.x < 0. || point.y < 0. || point.x as usize >= self.width || point.y as usize >= self.height {
+                continue;
+            }
+            let pixel = &mut self.pixels[point.y as usize * self.width + point.x as usize];
+            *pixel = blend(*pixel, line.color, line.opacity);
+        }
+    }
+
+    fn fill_circle(&mut self, cx: f32, cy: f32, radius: f32, color: Color) {
+        // Particles smaller than a pixel still take up one, otherwise zoomed out views would be empty
+        if radius < 0.5 {
+            if cx >= 0. && cy >= 0. && (cx as usize) < self.width && (cy as usize) < self.height {
+                self.pixels[cy as usize * self.width + cx as usize] = color;
+            }
+            return;
+        }
+
+        // Float to usize casts saturate, so circles partly or fully off screen end up with clipped or empty ranges
+        let (min_x, max_x) = ((cx - radius).floor() as usize, ((cx + radius).ceil() as usize).min(self.width));
+        let (min_y, max_y) = ((cy - radius).floor() as usize, ((cy + radius).ceil() as usize).min(self.height));
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+                if dx * dx + dy * dy <= radius * radius {
+                    self.pixels[y * self.width + x] = color;
+                }
+            }
+        }
+    }
+}
+
+impl Renderer for RasterRenderer {
+    type Error = Infallible;
+
+    fn render(&mut self, frame: &Frame) -> Result<(), Self::Error> {
+        self.width = frame.width.max(0.).round() as usize;
+        self.height = frame.height.max(0.).round() as usize;
+        self.pixels.clear();
+        self.pixels.resize(self.width * self.height, frame.background);
+
+        frame.geometry.iter().chain(frame.trails.iter()).for_each(|line| self.draw_line(line));
+        for particle in frame.particles.iter() {
+            self.fill_circle(particle.position.x, particle.position.y, particle.style.radius, particle.style.color);
+        }
+        frame.arrows.iter().for_each(|line| self.draw_line(line));
+        Ok(())
+    }
+}
+
+fn blend(below: Color, above: Color, opacity: f32) -> Color {
+    let opacity = opacity.clamp(0., 1.);
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * opacity).round() as u8;
+    Color::rgb(mix(below.r, above.r), mix(below.g, above.g), mix(below.b, above.b))
+}
@@ -0,0 +1,199 @@
+use std::{collections::VecDeque, f32::consts::TAU, str::FromStr};
+use sim_lib::{local_density, Bond, Obstacle, Particle, Point};
+use crate::Color;
+
+// Continuous colormaps are cut into this many steps so frames still batch into a handful of fills
+const COLORMAP_LEVELS: usize = 32;
+// Speeds and densities are normalized against this percentile so a few outliers don't wash out the rest
+const NORMALIZATION_PERCENTILE: f32 = 0.95;
+// Neighborhood used by the density mode, in world units
+const DENSITY_RADIUS: f32 = 0.5;
+const STILL_COLOR: Color = Color::rgb(128, 128, 128);
+const VIRIDIS: [Color; 5] = [
+    Color::rgb(68, 1, 84),
+    Color::rgb(59, 82, 139),
+    Color::rgb(33, 145, 140),
+    Color::rgb(94, 201, 98),
+    Color::rgb(253, 231, 37),
+];
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum ColorMode {
+    #[default]
+    Species,
+    Speed,
+    Direction,
+    Density,
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct FrameStyle<'a> {
+    pub color_mode: ColorMode,
+    pub arrows: bool,
+    pub boundary: bool,
+    pub obstacles: &'a [Obstacle],
+    pub bonds: &'a [Bond],
+    pub trails: Option<&'a Trails>,
+}
+
+// The last `length` positions of every particle, oldest first
+pub struct Trails {
+    length: usize,
+    history: VecDeque<Vec<Point>>,
+}
+
+impl ColorMode {
+    pub const ALL: &'static [ColorMode] = &[ColorMode::Species, ColorMode::Speed, ColorMode::Direction, ColorMode::Density];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            ColorMode::Species => "species",
+            ColorMode::Speed => "speed",
+            ColorMode::Direction => "direction",
+            ColorMode::Density => "density",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|m| m.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&m| m == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub(crate) fn colors(self, particles: &[Particle]) -> Vec<Color> {
+        match self {
+            ColorMode::Species => particles.iter().map(|p| p.color().into()).collect(),
+            ColorMode::Speed => {
+                normalized(particles.iter().map(|p| p.velocity().length()).collect()).into_iter().map(colormap).collect()
+            },
+            ColorMode::Direction => particles.iter().map(|p| direction_color(p.velocity().x, p.velocity().y)).collect(),
+            ColorMode::Density => {
+                normalized(local_density(particles, DENSITY_RADIUS).into_iter().map(|d| d as f32).collect()).into_iter().map(colormap).collect()
+            },
+        }
+    }
+}
+
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::from_name(name).ok_or_else(|| format!("Unknown color mode '{}'", name))
+    }
+}
+
+impl<'a> FrameStyle<'a> {
+    pub fn with_color_mode(mut self, color_mode: ColorMode) -> Self {
+        self.color_mode = color_mode;
+        self
+    }
+
+    pub fn with_arrows(mut self, arrows: bool) -> Self {
+        self.arrows = arrows;
+        self
+    }
+
+    pub fn with_boundary(mut self, boundary: bool) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    pub fn with_obstacles(mut self, obstacles: &'a [Obstacle]) -> Self {
+        self.obstacles = obstacles;
+        self
+    }
+
+    pub fn with_bonds(mut self, bonds: &'a [Bond]) -> Self {
+        self.bonds = bonds;
+        self
+    }
+
+    pub fn with_trails(mut self, trails: Option<&'a Trails>) -> Self {
+        self.trails = trails;
+        self
+    }
+}
+
+impl Trails {
+    pub fn new(length: usize) -> Self {
+        Trails {
+            length: usize::max(length, 2),
+            history: VecDeque::new(),
+        }
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+
+    // Call once per tick. A change in the particle count makes old positions meaningless, so it starts over.
+    pub fn record(&mut self, particles: &[Particle]) {
+        if self.history.front().is_some_and(|positions| positions.len() != particles.len()) {
+            self.history.clear();
+        }
+
+        let mut positions = if self.history.len() >= self.length {
+            self.history.pop_front().unwrap_or_default()
+        } else {
+            Vec::with_capacity(particles.len())
+        };
+        positions.clear();
+        positions.extend(particles.iter().map(|p| p.position()));
+        self.history.push_back(positions);
+    }
+
+    pub(crate) fn history(&self) -> &VecDeque<Vec<Point>> {
+        &self.history
+    }
+}
+
+// Scales values into 0..=1 against their percentile, leaves them unscaled when that percentile is zero
+fn normalized(mut values: Vec<f32>) -> Vec<f32> {
+    if values.is_empty() {
+        return values;
+    }
+
+    let mut partitioned = values.clone();
+    let rank = ((partitioned.len() - 1) as f32 * NORMALIZATION_PERCENTILE) as usize;
+    let (_, &mut scale, _) = partitioned.select_nth_unstable_by(rank, f32::total_cmp);
+    let scale = if scale.is_finite() && scale > 0. { scale } else { 1. };
+
+    values.iter_mut().for_each(|v| *v = f32::clamp(*v / scale, 0., 1.));
+    values
+}
+
+fn quantize(t: f32) -> f32 {
+    (t * (COLORMAP_LEVELS - 1) as f32).round() / (COLORMAP_LEVELS - 1) as f32
+}
+
+fn colormap(t: f32) -> Color {
+    let position = quantize(t) * (VIRIDIS.len() - 1) as f32;
+    let index = usize::min(position as usize, VIRIDIS.len() - 2);
+    let (from, to, t) = (VIRIDIS[index], VIRIDIS[index + 1], position - index as f32);
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color::rgb(lerp(from.r, to.r), lerp(from.g, to.g), lerp(from.b, to.b))
+}
+
+fn direction_color(x: f32, y: f32) -> Color {
+    if x == 0. && y == 0. {
+        return STILL_COLOR;
+    }
+    let hue = quantize(f32::atan2(y, x).rem_euclid(TAU) / TAU) * 6.;
+    let fraction = hue.fract();
+    let (rising, falling) = ((fraction * 255.).round() as u8, ((1. - fraction) * 255.).round() as u8);
+    match hue as usize % 6 {
+        0 => Color::rgb(255, rising, 0),
+        1 => Color::rgb(falling, 255, 0),
+        2 => Color::rgb(0, 255, rising),
+        3 => Color::rgb(0, falling, 255),
+        4 => Color::rgb(rising, 0, 255),
+        _ => Color::rgb(255, 0, falling),
+    }
+}
@@ -0,0 +1,65 @@
+use std::io::{self, Write};
+use crate::{Color, Frame, Line, Renderer};
+
+// Writes every rendered frame as a standalone SVG document
+pub struct SvgRenderer<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> SvgRenderer<W> {
+    pub fn new(writer: W) -> Self {
+        SvgRenderer { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Renderer for SvgRenderer<W> {
+    type Error = io::Error;
+
+    fn render(&mut self, frame: &Frame) -> Result<(), Self::Error> {
+        writeln!(
+            self.writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            frame.width, frame.height
+        )?;
+        writeln!(self.writer, r#"<rect width="100%" height="100%" fill="{}"/>"#, hex(frame.background))?;
+        self.write_lines(&frame.geometry)?;
+        self.write_lines(&frame.trails)?;
+
+        for particle in frame.particles.iter() {
+            writeln!(
+                self.writer,
+                r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}"/>"#,
+                particle.position.x, particle.position.y, particle.style.radius, hex(particle.style.color)
+            )?;
+        }
+
+        self.write_lines(&frame.arrows)?;
+        writeln!(self.writer, "</svg>")?;
+        self.writer.flush()
+    }
+}
+
+impl<W: Write> SvgRenderer<W> {
+    // One path element per batch of lines sharing a stroke
+    fn write_lines(&mut self, lines: &[Line]) -> io::Result<()> {
+        for batch in Frame::line_batches(lines) {
+            let data: Vec<String> = batch.lines.iter()
+                .map(|l| format!("M{:.2} {:.2}L{:.2} {:.2}", l.from.x, l.from.y, l.to.x, l.to.y))
+                .collect();
+            writeln!(
+                self.writer,
+                r#"<path d="{}" fill="none" stroke="{}" stroke-opacity="{:.3}" stroke-width="{}"/>"#,
+                data.concat(), hex(batch.color), batch.opacity, batch.width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
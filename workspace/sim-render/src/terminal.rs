@@ -0,0 +1,65 @@
+use std::io::{self, Write};
+use crate::{Color, Frame, RasterRenderer, Renderer};
+
+const UPPER_HALF_BLOCK: char = '▀';
+
+// Draws frames with 24-bit ANSI colors. Each character cell holds two pixels stacked vertically,
+// so a frame of `columns` x `2 * rows` pixels fills `columns` x `rows` cells.
+pub struct TerminalRenderer<W: Write> {
+    writer: W,
+    raster: RasterRenderer,
+    in_place: bool,
+}
+
+impl<W: Write> TerminalRenderer<W> {
+    pub fn new(writer: W) -> Self {
+        TerminalRenderer {
+            writer,
+            raster: RasterRenderer::new(),
+            in_place: false,
+        }
+    }
+
+    // Moves the cursor home before every frame so consecutive frames animate instead of scrolling
+    pub fn in_place(mut self) -> Self {
+        self.in_place = true;
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Renderer for TerminalRenderer<W> {
+    type Error = io::Error;
+
+    fn render(&mut self, frame: &Frame) -> Result<(), Self::Error> {
+        let Ok(()) = self.raster.render(frame);
+        let (width, height) = (self.raster.width(), self.raster.height());
+
+        let mut out = String::new();
+        if self.in_place {
+            out.push_str("\x1b[H");
+        }
+
+        for y in (0..height).step_by(2) {
+            for x in 0..width {
+                let top = self.raster.pixel(x, y);
+                let bottom = if y + 1 < height { self.raster.pixel(x, y + 1) } else { frame.background };
+                push_cell(&mut out, top, bottom);
+            }
+            out.push_str("\x1b[0m\n");
+        }
+
+        self.writer.write_all(out.as_bytes())?;
+        self.writer.flush()
+    }
+}
+
+fn push_cell(out: &mut String, top: Color, bottom: Color) {
+    out.push_str(&format!(
+        "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m{}",
+        top.r, top.g, top.b, bottom.r, bottom.g, bottom.b, UPPER_HALF_BLOCK
+    ));
+}
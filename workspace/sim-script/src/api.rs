@@ -0,0 +1,164 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, FLOAT, INT};
+use sim_lib::{
+    ForcesConfig, PairRadii, Particle, ParticleColor, PhysicsParams, Point, Vector, World,
+    WORLD_HEIGHT_FLOAT, WORLD_WIDTH_FLOAT,
+};
+
+pub(crate) struct WorldState {
+    pub particles: Vec<Particle>,
+    pub removed: Vec<bool>,
+    pub spawned: Vec<Particle>,
+    pub forces: ForcesConfig,
+    pub params: PhysicsParams,
+    pub tick: u64,
+    pub memory: Map,
+}
+
+// Script-side view of the world. Changes are buffered and written back once the script function returns,
+// so particle indices stay stable for the whole call even when particles are spawned or removed.
+#[derive(Clone)]
+pub(crate) struct ScriptWorld(pub Rc<RefCell<WorldState>>);
+
+#[derive(Clone)]
+pub(crate) struct ScriptParticle {
+    index: INT,
+    particle: Particle,
+}
+
+impl ScriptWorld {
+    pub fn new() -> Self {
+        ScriptWorld(Rc::new(RefCell::new(WorldState {
+            particles: Vec::new(),
+            removed: Vec::new(),
+            spawned: Vec::new(),
+            forces: ForcesConfig::empty(),
+            params: PhysicsParams::default(),
+            tick: 0,
+            memory: Map::new(),
+        })))
+    }
+
+    pub fn load_from(&self, world: &World) {
+        let mut state = self.0.borrow_mut();
+        state.particles = world.particles().to_vec();
+        state.removed = vec![false; state.particles.len()];
+        state.spawned.clear();
+        state.forces = world.get_forces_config();
+        state.params = world.get_physics_params();
+        state.tick = world.ticks();
+    }
+
+    pub fn store_into(&self, world: &mut World) {
+        let mut state = self.0.borrow_mut();
+        let spawned = std::mem::take(&mut state.spawned);
+        // In place first so bonds follow the particles through the removals
+        world.set_particles(state.particles.clone());
+        world.remove_particles(&state.removed);
+        world.set_particles(world.particles().iter().copied().chain(spawned).collect());
+        world.set_forces_config(state.forces);
+        world.set_physics_params(state.params);
+    }
+
+    pub fn particle(&self, index: usize) -> ScriptParticle {
+        ScriptParticle { index: index as INT, particle: self.0.borrow().particles[index] }
+    }
+
+    fn checked_index(&self, index: INT) -> Result<usize, Box<EvalAltResult>> {
+        let len = self.0.borrow().particles.len();
+        usize::try_from(index).ok()
+            .filter(|&i| i < len)
+            .ok_or_else(|| format!("Particle index {} out of range (0..{})", index, len).into())
+    }
+}
+
+pub(crate) fn register(engine: &mut Engine) {
+    engine.register_type_with_name::<ScriptParticle>("Particle")
+        .register_get("index", |p: &mut ScriptParticle| p.index)
+        .register_get_set("x", |p: &mut ScriptParticle| p.particle.position().x as FLOAT, |p: &mut ScriptParticle, x: FLOAT| {
+            p.particle = p.particle.with_position(Point::new(x as f32, p.particle.position().y));
+        })
+        .register_get_set("y", |p: &mut ScriptParticle| p.particle.position().y as FLOAT, |p: &mut ScriptParticle, y: FLOAT| {
+            p.particle = p.particle.with_position(Point::new(p.particle.position().x, y as f32));
+        })
+        .register_get_set("vx", |p: &mut ScriptParticle| p.particle.velocity().x as FLOAT, |p: &mut ScriptParticle, vx: FLOAT| {
+            p.particle = p.particle.with_velocity(Vector::new(vx as f32, p.particle.velocity().y));
+        })
+        .register_get_set("vy", |p: &mut ScriptParticle| p.particle.velocity().y as FLOAT, |p: &mut ScriptParticle, vy: FLOAT| {
+            p.particle = p.particle.with_velocity(Vector::new(p.particle.velocity().x, vy as f32));
+        })
+        .register_get("color", |p: &mut ScriptParticle| p.particle.color().name().to_string())
+        .register_set("color", |p: &mut ScriptParticle, color: String| -> Result<(), Box<EvalAltResult>> {
+            p.particle = p.particle.with_color(parse_color(&color)?);
+            Ok(())
+        });
+
+    engine.register_type_with_name::<ScriptWorld>("World")
+        .register_get("tick", |w: &mut ScriptWorld| w.0.borrow().tick as INT)
+        .register_get("width", |_: &mut ScriptWorld| WORLD_WIDTH_FLOAT as FLOAT)
+        .register_get("height", |_: &mut ScriptWorld| WORLD_HEIGHT_FLOAT as FLOAT)
+        .register_get_set("memory", |w: &mut ScriptWorld| w.0.borrow().memory.clone(), |w: &mut ScriptWorld, memory: Map| {
+            w.0.borrow_mut().memory = memory;
+        })
+        .register_get_set("friction", |w: &mut ScriptWorld| w.0.borrow().params.friction as FLOAT, |w: &mut ScriptWorld, friction: FLOAT| {
+            w.0.borrow_mut().params.friction = friction as f32;
+        })
+        .register_get_set("repel_radius", |w: &mut ScriptWorld| w.0.borrow().params.repel_radius as FLOAT, |w: &mut ScriptWorld, radius: FLOAT| {
+            w.0.borrow_mut().params.repel_radius = radius as f32;
+        })
+        .register_fn("colors", |_: &mut ScriptWorld| -> Array {
+            ParticleColor::ALL.iter().map(|c| Dynamic::from(c.name().to_string())).collect()
+        })
+        .register_fn("len", |w: &mut ScriptWorld| w.0.borrow().particles.len() as INT)
+        .register_fn("get", |w: &mut ScriptWorld, index: INT| -> Result<ScriptParticle, Box<EvalAltResult>> {
+            Ok(w.particle(w.checked_index(index)?))
+        })
+        .register_fn("set", |w: &mut ScriptWorld, index: INT, p: ScriptParticle| -> Result<(), Box<EvalAltResult>> {
+            let index = w.checked_index(index)?;
+            w.0.borrow_mut().particles[index] = p.particle;
+            Ok(())
+        })
+        .register_fn("remove", |w: &mut ScriptWorld, index: INT| -> Result<(), Box<EvalAltResult>> {
+            let index = w.checked_index(index)?;
+            w.0.borrow_mut().removed[index] = true;
+            Ok(())
+        })
+        .register_fn("add_particle", |w: &mut ScriptWorld, x: FLOAT, y: FLOAT, vx: FLOAT, vy: FLOAT, color: &str| -> Result<(), Box<EvalAltResult>> {
+            let particle = Particle::new(Point::new(x as f32, y as f32), Vector::new(vx as f32, vy as f32), parse_color(color)?);
+            w.0.borrow_mut().spawned.push(particle);
+            Ok(())
+        })
+        .register_fn("get_force", |w: &mut ScriptWorld, who: &str, to: &str| -> Result<FLOAT, Box<EvalAltResult>> {
+            Ok(w.0.borrow().forces.get(parse_color(who)?, parse_color(to)?) as FLOAT)
+        })
+        .register_fn("set_force", |w: &mut ScriptWorld, who: &str, to: &str, force: FLOAT| -> Result<(), Box<EvalAltResult>> {
+            let (who, to) = (parse_color(who)?, parse_color(to)?);
+            let mut state = w.0.borrow_mut();
+            state.forces = state.forces.with_force(who, to, force as f32);
+            Ok(())
+        })
+        .register_fn("set_radii", |w: &mut ScriptWorld, who: &str, to: &str, repel: FLOAT, interaction: FLOAT| -> Result<(), Box<EvalAltResult>> {
+            let (who, to) = (parse_color(who)?, parse_color(to)?);
+            if !(repel > 0. && repel < interaction) {
+                return Err("set_radii needs 0 < repel < interaction".into());
+            }
+            let mut state = w.0.borrow_mut();
+            state.forces = state.forces.with_radii(who, to, PairRadii { repel: repel as f32, interaction: interaction as f32 });
+            Ok(())
+        });
+}
+
+pub(crate) fn parse_acceleration(value: Dynamic) -> Result<Vector, Box<EvalAltResult>> {
+    let components = value.into_typed_array::<FLOAT>()?;
+    match components.as_slice() {
+        [x, y] => Ok(Vector::new(*x as f32, *y as f32)),
+        _ => Err("force() must return [ax, ay]".into()),
+    }
+}
+
+fn parse_color(name: &str) -> Result<ParticleColor, Box<EvalAltResult>> {
+    ParticleColor::from_name(name).ok_or_else(|| format!("Unknown color '{}'", name).into())
+}
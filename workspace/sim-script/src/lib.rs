@@ -0,0 +1,117 @@
+mod api;
+
+use std::{
+    fmt::{self, Display},
+    path::Path,
+};
+use rhai::{Dynamic, Engine, EvalAltResult, Scope, AST};
+use sim_lib::{CalculationMethod, World};
+use api::ScriptWorld;
+
+// Hooks a script may define, all optional:
+//   fn init(world)        once, before the first tick
+//   fn force(world, p)    per particle before every tick, returns [ax, ay], added to its velocity times dt
+//   fn on_tick(world)     after every tick
+// `world` exposes tick, width, height, friction, repel_radius, memory (a map kept between calls), colors(), len(),
+// get(i), set(i, p), remove(i), add_particle(x, y, vx, vy, color), get_force(who, to), set_force(who, to, value)
+// and set_radii(who, to, repel, interaction).
+// Particles expose index, x, y, vx, vy and color. Colors are names such as "Red".
+pub struct ScriptHost {
+    engine: Engine,
+    ast: AST,
+    world: ScriptWorld,
+    has_init: bool,
+    has_force: bool,
+    has_on_tick: bool,
+}
+
+#[derive(Debug)]
+pub struct ScriptError(String);
+
+impl ScriptHost {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ScriptError> {
+        let source = std::fs::read_to_string(&path)
+            .map_err(|e| ScriptError(format!("Could not read {}: {}", path.as_ref().display(), e)))?;
+        Self::from_source(&source)
+    }
+
+    pub fn from_source(source: &str) -> Result<Self, ScriptError> {
+        let mut engine = Engine::new();
+        api::register(&mut engine);
+
+        let ast = engine.compile(source).map_err(|e| ScriptError(e.to_string()))?;
+        let has_function = |name: &str, params: usize| ast.iter_functions().any(|f| f.name == name && f.params.len() == params);
+
+        Ok(ScriptHost {
+            has_init: has_function("init", 1),
+            has_force: has_function("force", 2),
+            has_on_tick: has_function("on_tick", 1),
+            engine,
+            ast,
+            world: ScriptWorld::new(),
+        })
+    }
+
+    pub fn init(&mut self, world: &mut World) -> Result<(), ScriptError> {
+        self.engine.run_ast(&self.ast)?;
+
+        if self.has_init {
+            self.call_world_hook(world, "init")?;
+        }
+        Ok(())
+    }
+
+    // Returns the substeps the world took, see `World::tick`
+    pub fn tick(&mut self, world: &mut World, calculation_method: &CalculationMethod, dt: f32) -> Result<u32, ScriptError> {
+        if self.has_force {
+            self.apply_script_force(world, dt)?;
+        }
+
+        let substeps = world.tick(calculation_method, dt);
+
+        if self.has_on_tick {
+            self.call_world_hook(world, "on_tick")?;
+        }
+        Ok(substeps)
+    }
+
+    fn call_world_hook(&mut self, world: &mut World, name: &str) -> Result<(), ScriptError> {
+        self.world.load_from(world);
+        // Hooks are called for their side effects on the world, whatever they evaluate to is ignored
+        let _ = self.engine.call_fn::<Dynamic>(&mut Scope::new(), &self.ast, name, (self.world.clone(),))?;
+        self.world.store_into(world);
+        Ok(())
+    }
+
+    fn apply_script_force(&mut self, world: &mut World, dt: f32) -> Result<(), ScriptError> {
+        self.world.load_from(world);
+        let mut scope = Scope::new();
+
+        let len = self.world.0.borrow().particles.len();
+        for i in 0..len {
+            let result = self.engine.call_fn::<Dynamic>(&mut scope, &self.ast, "force", (self.world.clone(), self.world.particle(i)))?;
+            let acceleration = api::parse_acceleration(result)?;
+
+            let mut state = self.world.0.borrow_mut();
+            let particle = state.particles[i];
+            state.particles[i] = particle.with_velocity(particle.velocity() + acceleration * dt);
+        }
+
+        self.world.store_into(world);
+        Ok(())
+    }
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Script error: {}", self.0)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+impl From<Box<EvalAltResult>> for ScriptError {
+    fn from(e: Box<EvalAltResult>) -> Self {
+        ScriptError(e.to_string())
+    }
+}
@@ -0,0 +1,60 @@
+use std::{convert::Infallible, f64::consts::TAU};
+use web_sys::CanvasRenderingContext2d;
+use sim_render::{Color, Frame, Line, Renderer};
+
+pub struct CanvasRenderer<'a> {
+    context: &'a CanvasRenderingContext2d,
+}
+
+impl<'a> CanvasRenderer<'a> {
+    pub fn new(context: &'a CanvasRenderingContext2d) -> Self {
+        CanvasRenderer { context }
+    }
+}
+
+impl Renderer for CanvasRenderer<'_> {
+    type Error = Infallible;
+
+    fn render(&mut self, frame: &Frame) -> Result<(), Self::Error> {
+        self.context.set_fill_style_str(&css(frame.background));
+        self.context.fill_rect(0., 0., frame.width as f64, frame.height as f64);
+        self.stroke_lines(&frame.geometry);
+        self.stroke_lines(&frame.trails);
+
+        // One path per color, a fill call per particle is what makes canvas drawing slow
+        for batch in frame.batches() {
+            self.context.begin_path();
+            for particle in batch.particles {
+                let (x, y, radius) = (particle.position.x as f64, particle.position.y as f64, f64::max(particle.style.radius as f64, 1.));
+                self.context.move_to(x + radius, y);
+                let _ = self.context.arc(x, y, radius, 0., TAU);
+            }
+            self.context.set_fill_style_str(&css(batch.color));
+            self.context.fill();
+        }
+
+        self.stroke_lines(&frame.arrows);
+        Ok(())
+    }
+}
+
+impl CanvasRenderer<'_> {
+    fn stroke_lines(&self, lines: &[Line]) {
+        for batch in Frame::line_batches(lines) {
+            self.context.begin_path();
+            for line in batch.lines {
+                self.context.move_to(line.from.x as f64, line.from.y as f64);
+                self.context.line_to(line.to.x as f64, line.to.y as f64);
+            }
+            self.context.set_stroke_style_str(&css(batch.color));
+            self.context.set_global_alpha(batch.opacity as f64);
+            self.context.set_line_width(batch.width as f64);
+            self.context.stroke();
+        }
+        self.context.set_global_alpha(1.);
+    }
+}
+
+fn css(color: Color) -> String {
+    format!("rgb({}, {}, {})", color.r, color.g, color.b)
+}
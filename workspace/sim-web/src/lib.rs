@@ -0,0 +1,77 @@
+mod canvas;
+
+use rand::{rngs::StdRng, SeedableRng};
+use wasm_bindgen::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+use sim_lib::{CalculationMethod, ForcesConfig, ParticleColor, PhysicsMode, Scenario, World};
+use sim_render::{Frame, Renderer, Viewport};
+use canvas::CanvasRenderer;
+
+// The browser gets a single thread, keep the default world small enough for 60 fps
+const DEFAULT_PARTICLES_PER_SPECIES: usize = 200;
+const MIN_RANDOM_FORCE: f32 = -1.;
+const MAX_RANDOM_FORCE: f32 = 1.;
+
+// An experiment is fully described by its scenario text and seed, which is what goes into shared links. Spawns,
+// respawns and thermostat noise all come from the seed, so a shared link replays tick for tick.
+#[wasm_bindgen]
+pub struct Simulation {
+    scenario: Scenario,
+    seed: u32,
+    world: World,
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    #[wasm_bindgen(constructor)]
+    pub fn new(scenario: &str, seed: u32) -> Result<Simulation, JsError> {
+        let scenario: Scenario = scenario.parse()?;
+        Ok(Self::from_scenario(scenario, seed))
+    }
+
+    pub fn random(seed: u32) -> Simulation {
+        let forces = ForcesConfig::random_with(&mut StdRng::seed_from_u64(seed as u64), MIN_RANDOM_FORCE, MAX_RANDOM_FORCE);
+        let scenario = ParticleColor::ALL.iter().fold(Scenario::new(PhysicsMode::Emergence, forces), |s, &color| {
+            s.with_spawn(color, DEFAULT_PARTICLES_PER_SPECIES)
+        });
+        Self::from_scenario(scenario, seed)
+    }
+
+    pub fn scenario(&self) -> String {
+        self.scenario.to_string()
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    pub fn ticks(&self) -> f64 {
+        self.world.ticks() as f64
+    }
+
+    pub fn particles_count(&self) -> usize {
+        self.world.particles().len()
+    }
+
+    pub fn reset(&mut self) {
+        self.world = self.scenario.build_world_seeded(self.seed as u64);
+    }
+
+    // Returns the substeps the ticks took
+    pub fn tick(&mut self, n: u32) -> u32 {
+        (0..n).map(|_| self.world.tick(&CalculationMethod::SingleThread, 1.)).sum()
+    }
+
+    // Fits the whole world into the canvas, keeping its aspect ratio
+    pub fn draw(&self, context: &CanvasRenderingContext2d, width: f32, height: f32) {
+        let frame = Frame::from_world(&self.world, &Viewport::fit_world(width, height));
+        let Ok(()) = CanvasRenderer::new(context).render(&frame);
+    }
+}
+
+impl Simulation {
+    fn from_scenario(scenario: Scenario, seed: u32) -> Self {
+        let world = scenario.build_world_seeded(seed as u64);
+        Simulation { scenario, seed, world }
+    }
+}
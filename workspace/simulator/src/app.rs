@@ -1,20 +1,48 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use femtovg::Canvas;
 use winit::keyboard::KeyCode;
-use sim_lib::{World, ForcesConfig, Point, ThreadPool, CalculationMethod, GpuExecutor};
+use sim_lib::{World, ForcesConfig, Point, CalculationMethod, GpuExecutor, Replay};
+use sim_script::ScriptHost;
+use sim_render::{ColorMode, FemtovgRenderer, Frame, FrameStyle, Renderer, Trails};
 use crate::{
+    camera::Camera,
     constants,
+    selection::Selection,
     timer::Timer,
 };
 
 pub struct App {
     pub world: World,
     pub calculation_method: CalculationMethod,
-    pub camera_position: Point,
-    pub camera_scale_factor: f32,
+    pub camera: Camera,
     pub default_forces_config: ForcesConfig,
     pub tick_timer: Timer,
     pub draw_timer: Timer,
+    pub frame_timer: Timer,
+    pub last_frame: Option<Instant>,
+    pub stats: Stats,
+    pub replay: Option<ReplayControl>,
+    pub script: Option<ScriptHost>,
+    pub color_mode: ColorMode,
+    pub arrows: bool,
+    pub trails: Option<Trails>,
+    pub selection: Selection,
+}
+
+// Latest averages from the timers, they only change once every timer chunk
+#[derive(Default)]
+pub struct Stats {
+    pub fps: Option<f32>,
+    pub tick_time: Option<Duration>,
+    pub draw_time: Option<Duration>,
+    // Taken by the last tick, not averaged
+    pub substeps: Option<u32>,
+}
+
+pub struct ReplayControl {
+    pub replay: Replay,
+    pub playing: bool,
+    pub direction: isize,
 }
 
 impl App {
@@ -23,52 +51,191 @@ impl App {
         App {
             world,
             calculation_method: CalculationMethod::GPU(GpuExecutor::new()),
-            camera_position: Point::new(0., 0.),
-            camera_scale_factor: 1.,
+            camera: Camera::new(),
             default_forces_config,
             tick_timer: Timer::new(50),
             draw_timer: Timer::new(50),
+            frame_timer: Timer::new(50),
+            last_frame: None,
+            stats: Stats::default(),
+            replay: None,
+            script: None,
+            color_mode: ColorMode::default(),
+            arrows: false,
+            trails: None,
+            selection: Selection::new(),
         }
     }
 
+    pub fn new_replay(replay: Replay) -> Self {
+        let world = World::new(replay.particles(), ForcesConfig::empty(), replay.physics_mode());
+        let mut app = Self::new(world);
+        app.replay = Some(ReplayControl {
+            replay,
+            playing: true,
+            direction: 1,
+        });
+        app
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    pub fn advance_replay(&mut self) {
+        let Some(control) = self.replay.as_mut() else {
+            return;
+        };
+
+        if control.playing {
+            if let Err(e) = control.replay.step(control.direction) {
+                eprintln!("Could not read replay frame: {}, pausing", e);
+                control.playing = false;
+                return;
+            }
+            self.world.set_particles(control.replay.particles());
+            self.record_trails();
+        }
+    }
+
+    pub fn update_replay(&mut self, request: ReplayRequest) {
+        let Some(control) = self.replay.as_mut() else {
+            return;
+        };
+
+        let frames = match request {
+            ReplayRequest::TogglePause => {
+                control.playing = !control.playing;
+                return;
+            },
+            ReplayRequest::Reverse => {
+                control.direction = -control.direction;
+                return;
+            },
+            ReplayRequest::StepForward => 1,
+            ReplayRequest::StepBackward => -1,
+            ReplayRequest::JumpForward => constants::REPLAY_JUMP_FRAMES,
+            ReplayRequest::JumpBackward => -constants::REPLAY_JUMP_FRAMES,
+        };
+
+        control.playing = false;
+        if let Err(e) = control.replay.step(frames) {
+            eprintln!("Could not read replay frame: {}", e);
+            return;
+        }
+        self.world.set_particles(control.replay.particles());
+        // Stepping around a replay would connect unrelated positions
+        if let Some(trails) = self.trails.as_mut() {
+            trails.clear();
+        }
+        eprintln!("Replay frame {}/{} (tick {})", control.replay.position() + 1, control.replay.len(), control.replay.tick());
+    }
+
+    pub fn attach_script(&mut self, mut script: ScriptHost) -> Result<(), sim_script::ScriptError> {
+        script.init(&mut self.world)?;
+        self.script = Some(script);
+        Ok(())
+    }
+
     pub fn single_world_tick(&mut self) {
         let measurement = self.tick_timer.start();
-        self.world.tick(&self.calculation_method);
+        match self.script.as_mut() {
+            Some(script) => match script.tick(&mut self.world, &self.calculation_method, 1.) {
+                Ok(substeps) => self.stats.substeps = Some(substeps),
+                Err(e) => {
+                    eprintln!("{}, detaching script", e);
+                    self.script = None;
+                },
+            },
+            None => self.stats.substeps = Some(self.world.tick(&self.calculation_method, 1.)),
+        }
+        self.selection.sync(self.world.particles().len());
         measurement.end();
+        self.record_trails();
+    }
+
+    fn record_trails(&mut self) {
+        if let Some(trails) = self.trails.as_mut() {
+            trails.record(self.world.particles());
+        }
     }
 
     pub fn draw_world<R: femtovg::Renderer>(&mut self, canvas: &mut Canvas<R>) {
+        let viewport = self.camera.viewport(canvas.width() as f32, canvas.height() as f32);
         let measurement = self.draw_timer.start();
-        self.world.draw(canvas, self.camera_position, self.camera_scale_factor);
+        let style = FrameStyle::default()
+            .with_color_mode(self.color_mode)
+            .with_arrows(self.arrows)
+            .with_boundary(true)
+            .with_obstacles(self.world.obstacles())
+            .with_bonds(self.world.bonds())
+            .with_trails(self.trails.as_ref());
+        let Ok(()) = FemtovgRenderer::new(canvas).render(&Frame::styled(self.world.particles(), &viewport, &style));
         measurement.end();
     }
 
-    pub fn update_camera_position(&mut self, request: CameraMoveRequest) {
-        match request {
-            CameraMoveRequest::Down => self.camera_position.y += constants::CAMERA_MOVEMENT_SENSITIVITY / self.camera_scale_factor,
-            CameraMoveRequest::Up => self.camera_position.y -= constants::CAMERA_MOVEMENT_SENSITIVITY / self.camera_scale_factor,
-            CameraMoveRequest::Right => self.camera_position.x += constants::CAMERA_MOVEMENT_SENSITIVITY / self.camera_scale_factor,
-            CameraMoveRequest::Left => self.camera_position.x -= constants::CAMERA_MOVEMENT_SENSITIVITY / self.camera_scale_factor,
-        }
+    // Index of the particle closest to a world position
+    pub fn particle_nearest(&self, position: Point) -> Option<usize> {
+        self.world.particles().iter()
+            .map(|p| (p.position() - position).square_length())
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)
     }
 
-    pub fn update_camera_zoom(&mut self, request: CameraZoomRequest) {
-        let diff = match request {
-            CameraZoomRequest::In => constants::CAMERA_ZOOM_SENSITIVITY,
-            CameraZoomRequest::Out => -constants::CAMERA_ZOOM_SENSITIVITY,
+    // Follows the particle closest to `position`, or everything clustered with it
+    pub fn follow_nearest(&mut self, position: Point, whole_cluster: bool) {
+        let Some(nearest) = self.particle_nearest(position) else {
+            return;
         };
-        self.camera_scale_factor = sim_lib::bounded_value(self.camera_scale_factor + diff, constants::MIN_CAMERA_SCALE_FACTOR, constants::MAX_CAMERA_SCALE_FACTOR)
+
+        let particles = self.world.particles();
+        let indices = if whole_cluster {
+            let labels = sim_lib::cluster_labels(particles, constants::FOLLOW_CLUSTER_RADIUS);
+            (0..particles.len()).filter(|&i| labels[i] == labels[nearest]).collect()
+        } else {
+            vec![nearest]
+        };
+        eprintln!("Following {} particle(s)", indices.len());
+        self.camera.follow(indices, particles.len());
     }
 
-    pub fn consume_world_tick_average_time(&mut self) -> Option<Duration> {
-        self.tick_timer.consume_average_time()
+    pub fn update_visuals(&mut self, request: VisualRequest) {
+        match request {
+            VisualRequest::NextColorMode => {
+                self.color_mode = self.color_mode.next();
+                eprintln!("Color mode: {}", self.color_mode.name());
+            },
+            VisualRequest::ToggleArrows => self.arrows = !self.arrows,
+            VisualRequest::ToggleTrails => {
+                self.trails = match self.trails {
+                    Some(_) => None,
+                    None => Some(Trails::new(constants::TRAIL_LENGTH)),
+                };
+            },
+        }
     }
 
-    pub fn consume_world_draw_average_time(&mut self) -> Option<Duration> {
-        self.draw_timer.consume_average_time()
+    // Call once per frame
+    pub fn update_stats(&mut self) {
+        let now = Instant::now();
+        if let Some(last_frame) = self.last_frame.replace(now) {
+            self.frame_timer.record(now - last_frame);
+        }
+
+        if let Some(frame_time) = self.frame_timer.consume_average_time() {
+            self.stats.fps = Some(1. / frame_time.as_secs_f32().max(f32::EPSILON));
+        }
+        if let Some(tick_time) = self.tick_timer.consume_average_time() {
+            self.stats.tick_time = Some(tick_time);
+        }
+        if let Some(draw_time) = self.draw_timer.consume_average_time() {
+            self.stats.draw_time = Some(draw_time);
+        }
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub enum CameraMoveRequest {
     Right,
     Left,
@@ -76,9 +243,19 @@ pub enum CameraMoveRequest {
     Down,
 }
 
-pub enum CameraZoomRequest {
-    In,
-    Out,
+pub enum VisualRequest {
+    NextColorMode,
+    ToggleArrows,
+    ToggleTrails,
+}
+
+pub enum ReplayRequest {
+    TogglePause,
+    Reverse,
+    StepForward,
+    StepBackward,
+    JumpForward,
+    JumpBackward,
 }
 
 impl TryFrom<KeyCode> for CameraMoveRequest {
@@ -94,3 +271,32 @@ impl TryFrom<KeyCode> for CameraMoveRequest {
         })
     }
 }
+
+impl TryFrom<KeyCode> for ReplayRequest {
+    type Error = ();
+
+    fn try_from(key: KeyCode) -> Result<Self, Self::Error> {
+        Ok(match key {
+            KeyCode::Space => ReplayRequest::TogglePause,
+            KeyCode::KeyR => ReplayRequest::Reverse,
+            KeyCode::Period => ReplayRequest::StepForward,
+            KeyCode::Comma => ReplayRequest::StepBackward,
+            KeyCode::BracketRight => ReplayRequest::JumpForward,
+            KeyCode::BracketLeft => ReplayRequest::JumpBackward,
+            _ => return Err(())
+        })
+    }
+}
+
+impl TryFrom<KeyCode> for VisualRequest {
+    type Error = ();
+
+    fn try_from(key: KeyCode) -> Result<Self, Self::Error> {
+        Ok(match key {
+            KeyCode::KeyC => VisualRequest::NextColorMode,
+            KeyCode::KeyV => VisualRequest::ToggleArrows,
+            KeyCode::KeyT => VisualRequest::ToggleTrails,
+            _ => return Err(())
+        })
+    }
+}
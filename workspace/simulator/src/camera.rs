@@ -0,0 +1,160 @@
+use std::{collections::HashSet, time::Instant};
+use sim_lib::{Particle, Point, Vector};
+use sim_render::Viewport;
+use crate::{app::CameraMoveRequest, constants};
+
+pub struct Camera {
+    // World position drawn at the top left corner
+    pub position: Point,
+    pub scale: f32,
+    // Screen pixels per second, so panning feels the same at any zoom
+    velocity: Vector,
+    held: HashSet<CameraMoveRequest>,
+    drag: Option<Point>,
+    follow: Option<Follow>,
+    last_update: Option<Instant>,
+}
+
+// Indices only mean something as long as the particle count stays the same
+struct Follow {
+    indices: Vec<usize>,
+    particles_count: usize,
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        Camera {
+            position: Point::new(0., 0.),
+            scale: 1.,
+            velocity: Vector::zero(),
+            held: HashSet::new(),
+            drag: None,
+            follow: None,
+            last_update: None,
+        }
+    }
+
+    pub fn viewport(&self, width: f32, height: f32) -> Viewport {
+        Viewport::new(self.position, self.scale, width, height)
+    }
+
+    pub fn set_moving(&mut self, request: CameraMoveRequest, moving: bool) {
+        if moving {
+            self.held.insert(request);
+            self.follow = None;
+        } else {
+            self.held.remove(&request);
+        }
+    }
+
+    // Key releases are not delivered while the window is unfocused
+    pub fn release_all(&mut self) {
+        self.held.clear();
+        self.drag = None;
+    }
+
+    // Call once per frame, before drawing
+    pub fn update(&mut self, particles: &[Particle], width: f32, height: f32) {
+        let now = Instant::now();
+        // Long pauses (window dragged, breakpoints) would otherwise fling the camera
+        let dt = self.last_update.replace(now).map_or(0., |last| (now - last).as_secs_f32().min(constants::CAMERA_MAX_FRAME_TIME));
+
+        let direction = self.held.iter().fold(Vector::zero(), |sum, request| sum + request.direction());
+        if direction != Vector::zero() {
+            self.velocity += direction.normalize() * constants::CAMERA_ACCELERATION * dt;
+            self.velocity = self.velocity.with_max_length(constants::CAMERA_MAX_SPEED);
+        } else {
+            self.velocity *= f32::exp(-constants::CAMERA_DAMPING * dt);
+            if self.velocity.length() < constants::CAMERA_MIN_SPEED {
+                self.velocity = Vector::zero();
+            }
+        }
+        self.position += self.velocity * dt / self.scale;
+
+        if let Some(target) = self.follow_target(particles) {
+            let center = self.position + Vector::new(width, height) / (2. * self.scale);
+            let smoothing = 1. - f32::exp(-constants::CAMERA_FOLLOW_STIFFNESS * dt);
+            self.position += (target - center) * smoothing;
+        }
+    }
+
+    // Zooms by `steps` wheel notches keeping the world position under `anchor` in place
+    pub fn zoom_at(&mut self, anchor: Point, steps: f32) {
+        let anchored = self.position + anchor.to_vector() / self.scale;
+        self.scale = sim_lib::bounded_value(
+            self.scale * constants::CAMERA_ZOOM_STEP.powf(steps),
+            constants::MIN_CAMERA_SCALE_FACTOR,
+            constants::MAX_CAMERA_SCALE_FACTOR,
+        );
+        self.position = anchored - anchor.to_vector() / self.scale;
+    }
+
+    pub fn start_drag(&mut self, cursor: Point) {
+        self.drag = Some(cursor);
+        self.velocity = Vector::zero();
+    }
+
+    pub fn drag_to(&mut self, cursor: Point) {
+        if let Some(last) = self.drag.replace(cursor) {
+            self.position -= (cursor - last) / self.scale;
+            self.follow = None;
+        }
+    }
+
+    pub fn end_drag(&mut self) {
+        self.drag = None;
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    pub fn fit_world(&mut self, width: f32, height: f32) {
+        let viewport = Viewport::fit_world(width, height);
+        self.position = viewport.camera;
+        self.scale = viewport.scale;
+        self.velocity = Vector::zero();
+        self.follow = None;
+    }
+
+    // Moves the camera so `position` ends up in the middle of a `width` x `height` view
+    pub fn center_on(&mut self, position: Point, width: f32, height: f32) {
+        self.position = position - Vector::new(width, height) / (2. * self.scale);
+        self.follow = None;
+    }
+
+    pub fn follow(&mut self, indices: Vec<usize>, particles_count: usize) {
+        self.follow = Some(Follow { indices, particles_count }).filter(|f| !f.indices.is_empty());
+    }
+
+    pub fn stop_following(&mut self) {
+        self.follow = None;
+    }
+
+    pub fn is_following(&self) -> bool {
+        self.follow.is_some()
+    }
+
+    // Centroid of the followed particles, stops following once the indices are stale
+    fn follow_target(&mut self, particles: &[Particle]) -> Option<Point> {
+        let follow = self.follow.as_ref()?;
+        if follow.particles_count != particles.len() {
+            self.follow = None;
+            return None;
+        }
+
+        let sum = follow.indices.iter().fold(Vector::zero(), |sum, &i| sum + particles[i].position().to_vector());
+        Some((sum / follow.indices.len() as f32).to_point())
+    }
+}
+
+impl CameraMoveRequest {
+    fn direction(&self) -> Vector {
+        match self {
+            CameraMoveRequest::Right => Vector::new(1., 0.),
+            CameraMoveRequest::Left => Vector::new(-1., 0.),
+            CameraMoveRequest::Up => Vector::new(0., -1.),
+            CameraMoveRequest::Down => Vector::new(0., 1.),
+        }
+    }
+}
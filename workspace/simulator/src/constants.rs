@@ -1,4 +1,19 @@
-pub const CAMERA_MOVEMENT_SENSITIVITY: f32 = 20.0;
-pub const CAMERA_ZOOM_SENSITIVITY: f32 = 0.05;
+// Camera panning speeds are in screen pixels per second
+pub const CAMERA_ACCELERATION: f32 = 4000.0;
+pub const CAMERA_MAX_SPEED: f32 = 1500.0;
+pub const CAMERA_MIN_SPEED: f32 = 5.0;
+pub const CAMERA_DAMPING: f32 = 10.0;
+pub const CAMERA_MAX_FRAME_TIME: f32 = 0.1;
+pub const CAMERA_FOLLOW_STIFFNESS: f32 = 8.0;
+// Scale factor change per mouse wheel notch
+pub const CAMERA_ZOOM_STEP: f32 = 1.1;
+// Trackpads scroll in pixels, this many count as one wheel notch
+pub const PIXELS_PER_ZOOM_STEP: f32 = 50.0;
 pub const MAX_CAMERA_SCALE_FACTOR: f32 = 3.0;
 pub const MIN_CAMERA_SCALE_FACTOR: f32 = 0.1;
+pub const REPLAY_JUMP_FRAMES: isize = 100;
+pub const TRAIL_LENGTH: usize = 20;
+// Particles closer than this, in world units, are followed together
+pub const FOLLOW_CLUSTER_RADIUS: f32 = 0.5;
+// A left button release within this many logical pixels of the press is a click
+pub const CLICK_TOLERANCE: f32 = 4.0;
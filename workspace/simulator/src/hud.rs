@@ -0,0 +1,187 @@
+use femtovg::{Canvas, Color, FontId, Paint, Path};
+use sim_lib::ParticleColor;
+use winit::keyboard::KeyCode;
+use crate::app::App;
+
+// Tried in order, the first one that loads is used. Without any of them the HUD stays hidden.
+const FONT_PATHS: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf",
+    "/usr/share/fonts/TTF/DejaVuSansMono.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSansMono.ttf",
+    "/System/Library/Fonts/Menlo.ttc",
+    "C:\\Windows\\Fonts\\consola.ttf",
+];
+const FONT_SIZE: f32 = 13.;
+const LINE_HEIGHT: f32 = 17.;
+const MARGIN: f32 = 10.;
+const PADDING: f32 = 8.;
+const PANEL_OPACITY: f32 = 0.6;
+const KEYBINDINGS: &[(&str, &str)] = &[
+    ("WASD / arrows", "move camera"),
+    ("mouse wheel", "zoom at cursor"),
+    ("drag", "pan"),
+    ("F", "fit whole world"),
+    ("G", "follow particle at cursor"),
+    ("Shift+G", "follow cluster at cursor"),
+    ("1", "random forces"),
+    ("2", "default forces"),
+    ("C", "cycle color mode"),
+    ("T", "toggle trails"),
+    ("V", "toggle velocity arrows"),
+    ("click", "inspect particle"),
+    ("Shift+drag", "box select"),
+    ("P", "recolor selection"),
+    ("X / Delete", "delete selection"),
+    ("Z", "pin / unpin selection"),
+    ("E", "export selection"),
+    ("Esc", "clear selection"),
+    ("M", "toggle minimap"),
+    ("click minimap", "jump camera there"),
+    ("Space", "replay: pause / resume"),
+    ("R", "replay: reverse"),
+    (", / .", "replay: step"),
+    ("[ / ]", "replay: jump"),
+    ("Tab", "toggle HUD"),
+    ("H", "toggle this help"),
+];
+
+pub struct Hud {
+    font: Option<FontId>,
+    visible: bool,
+    help_visible: bool,
+}
+
+pub enum HudRequest {
+    ToggleVisible,
+    ToggleHelp,
+}
+
+impl Hud {
+    pub fn new<R: femtovg::Renderer>(canvas: &mut Canvas<R>) -> Self {
+        let font = FONT_PATHS.iter().find_map(|path| canvas.add_font(path).ok());
+        if font.is_none() {
+            eprintln!("No HUD font found, looked in {:?}", FONT_PATHS);
+        }
+
+        Hud {
+            font,
+            visible: true,
+            help_visible: false,
+        }
+    }
+
+    pub fn update(&mut self, request: HudRequest) {
+        match request {
+            HudRequest::ToggleVisible => self.visible = !self.visible,
+            HudRequest::ToggleHelp => self.help_visible = !self.help_visible,
+        }
+    }
+
+    // `scale` is the window scale factor, the canvas is in physical pixels
+    pub fn draw<R: femtovg::Renderer>(&self, canvas: &mut Canvas<R>, app: &App, scale: f32) {
+        let Some(font) = self.font else {
+            return;
+        };
+        if !self.visible {
+            return;
+        }
+
+        let paint = Paint::color(Color::white()).with_font(&[font]).with_font_size(FONT_SIZE * scale);
+        let bottom = draw_panel(canvas, &paint, MARGIN * scale, MARGIN * scale, scale, &stats_lines(app));
+
+        if self.help_visible {
+            let lines: Vec<String> = KEYBINDINGS.iter().map(|(key, action)| format!("{:<14} {}", key, action)).collect();
+            draw_panel(canvas, &paint, MARGIN * scale, bottom + MARGIN * scale, scale, &lines);
+        }
+
+        if let Some(lines) = inspector_lines(app) {
+            let x = canvas.width() as f32 - panel_width(canvas, &paint, scale, &lines) - MARGIN * scale;
+            draw_panel(canvas, &paint, x, MARGIN * scale, scale, &lines);
+        }
+    }
+}
+
+impl TryFrom<KeyCode> for HudRequest {
+    type Error = ();
+
+    fn try_from(key: KeyCode) -> Result<Self, Self::Error> {
+        Ok(match key {
+            KeyCode::Tab => HudRequest::ToggleVisible,
+            KeyCode::KeyH => HudRequest::ToggleHelp,
+            _ => return Err(())
+        })
+    }
+}
+
+fn stats_lines(app: &App) -> Vec<String> {
+    let millis = |duration: Option<std::time::Duration>| match duration {
+        Some(duration) => format!("{:.1} ms", duration.as_secs_f32() * 1000.),
+        None => "-".to_string(),
+    };
+
+    let mut lines = vec![
+        format!("FPS       {}", app.stats.fps.map_or("-".to_string(), |fps| format!("{:.0}", fps))),
+        format!("tick      {}", millis(app.stats.tick_time)),
+        format!("substeps  {}", app.stats.substeps.map_or("-".to_string(), |substeps| substeps.to_string())),
+        format!("draw      {}", millis(app.stats.draw_time)),
+        format!("particles {}", app.world.particles().len()),
+        format!("method    {}", app.calculation_method.name()),
+        format!("physics   {}", app.world.physics_mode().name()),
+        format!("camera    {:.0}, {:.0}{}", app.camera.position.x, app.camera.position.y, if app.camera.is_following() { " following" } else { "" }),
+        format!("zoom      {:.2}x", app.camera.scale),
+        format!("colors    {}", app.color_mode.name()),
+    ];
+
+    if !app.selection.indices().is_empty() {
+        lines.push(format!("selected  {}", app.selection.indices().len()));
+    }
+    if let Some(control) = &app.replay {
+        lines.push(format!("replay    {}/{}{}", control.replay.position() + 1, control.replay.len(), if control.playing { "" } else { " paused" }));
+    }
+    lines.push("H for help".to_string());
+    lines
+}
+
+fn inspector_lines(app: &App) -> Option<Vec<String>> {
+    let index = app.selection.inspected.filter(|&i| i < app.world.particles().len())?;
+    let particle = app.world.particles()[index];
+    let (position, velocity) = (particle.position(), particle.velocity());
+
+    let mut lines = vec![
+        format!("particle  #{} {}{}", index, particle.color().name(), if particle.is_pinned() { " (pinned)" } else { "" }),
+        format!("position  {:.1}, {:.1}", position.x, position.y),
+        format!("velocity  {:+.3}, {:+.3}", velocity.x, velocity.y),
+        format!("speed     {:.3}", velocity.length()),
+        "acceleration by species".to_string(),
+    ];
+    let contributions = app.world.acceleration_contributions(index);
+    lines.extend(ParticleColor::ALL.iter().map(|&color| {
+        let acc = contributions[color as usize];
+        format!("  {:<7} {:+.4}, {:+.4}", color.name(), acc.x, acc.y)
+    }));
+    Some(lines)
+}
+
+fn panel_width<R: femtovg::Renderer>(canvas: &Canvas<R>, paint: &Paint, scale: f32, lines: &[String]) -> f32 {
+    lines.iter()
+        .filter_map(|line| canvas.measure_text(0., 0., line, paint).ok())
+        .map(|metrics| metrics.width())
+        .fold(0., f32::max) + PADDING * scale * 2.
+}
+
+// Returns the bottom edge of the panel
+fn draw_panel<R: femtovg::Renderer>(canvas: &mut Canvas<R>, paint: &Paint, x: f32, y: f32, scale: f32, lines: &[String]) -> f32 {
+    let width = panel_width(canvas, paint, scale, lines);
+    let (padding, line_height) = (PADDING * scale, LINE_HEIGHT * scale);
+    let height = lines.len() as f32 * line_height + padding * 2.;
+
+    let mut background = Path::new();
+    background.rect(x, y, width, height);
+    canvas.fill_path(&background, &Paint::color(Color::rgbaf(0., 0., 0., PANEL_OPACITY)));
+
+    for (i, line) in lines.iter().enumerate() {
+        // Text is positioned by its baseline
+        let _ = canvas.fill_text(x + padding, y + padding + (i as f32 + 0.8) * line_height, line, paint);
+    }
+    y + height
+}
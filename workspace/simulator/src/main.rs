@@ -2,25 +2,55 @@ mod graphics;
 mod constants;
 mod app;
 mod timer;
+mod options;
+mod hud;
+mod minimap;
+mod camera;
+mod selection;
 
-use sim_lib::{ParticleColor, Particle, Point, Vector, World, ForcesConfig, PhysicsMode};
-use femtovg::Color;
+use sim_lib::{ParticleColor, Particle, Point, Vector, World, ForcesConfig, PhysicsMode, Recorder, Replay, Scenario};
 use graphics::Context as GraphicsContext;
-use app::{App, CameraZoomRequest};
+use app::{App, CameraMoveRequest, ReplayRequest, VisualRequest};
+use options::Options;
+use hud::{Hud, HudRequest};
+use minimap::Minimap;
+use selection::SelectionRequest;
+use sim_script::ScriptHost;
 use winit::{
-    event::{ElementState, Event, MouseScrollDelta, TouchPhase, WindowEvent},
-    keyboard::KeyCode
+    event::{ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{KeyCode, ModifiersState},
 };
 
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let options = Options::from_args()?;
     let app_context = graphics::init();
-    run(app_context)?;
+    run(app_context, options)?;
     Ok(())
 }
 
-fn run(mut graphics_context: GraphicsContext) -> Result<(), Box<dyn std::error::Error>> {
-    let mut app = App::new(get_emergence_world());
+fn run(mut graphics_context: GraphicsContext, options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    let mut app = match (&options.replay_path, &options.scenario_path) {
+        (Some(path), _) => App::new_replay(Replay::open(path)?),
+        (None, Some(path)) => App::new(Scenario::load(path)?.build_world()),
+        (None, None) => App::new(get_emergence_world()),
+    };
+
+    if let Some(path) = &options.script_path {
+        app.attach_script(ScriptHost::load(path)?)?;
+    }
+
+    if let Some(path) = &options.record_path {
+        app.world.start_recording(Recorder::create(path, options.record_interval)?)?;
+    }
+
+    let mut hud = Hud::new(&mut graphics_context.canvas);
+    let mut minimap = Minimap::new();
+    let mut cursor_position = Point::new(0., 0.);
+    let mut modifiers = ModifiersState::empty();
+    // Where the left button went down, a release close to it is a click rather than a drag
+    let mut press_position: Option<Point> = None;
+    let mut box_start: Option<Point> = None;
 
     let ticker_thread_window = graphics_context.window.clone();
     std::thread::spawn(move || {
@@ -31,68 +61,127 @@ fn run(mut graphics_context: GraphicsContext) -> Result<(), Box<dyn std::error::
     });
 
     graphics_context.event_loop
-        .run(move |event, event_target_window| match event {
-            Event::WindowEvent { event, .. } => match event {
-                WindowEvent::CloseRequested => event_target_window.exit(),
-                WindowEvent::RedrawRequested { .. } => {
-                    app.single_world_tick();
-
-                    if let Some(avg_duration) = app.consume_world_tick_average_time() {
-                        println!("Average tick time: {} milliseconds", avg_duration.as_millis());
+        .run(move |event, event_target_window| if let Event::WindowEvent { event, .. } = event {
+            match event {
+                WindowEvent::CloseRequested => {
+                    if let Err(e) = app.world.stop_recording().and_then(|recorder| recorder.map_or(Ok(()), |mut r| r.flush())) {
+                        eprintln!("Recording failed: {}", e);
+                    }
+                    event_target_window.exit();
+                },
+                WindowEvent::RedrawRequested => {
+                    if app.is_replaying() {
+                        app.advance_replay();
+                    } else {
+                        app.single_world_tick();
                     }
 
                     let size = graphics_context.window.inner_size();
-                    graphics_context.canvas.set_size(size.width, size.height, graphics_context.window.scale_factor() as f32);
-                    graphics_context.canvas.clear_rect(0, 0, size.width, size.height, Color::black());
+                    let scale_factor = graphics_context.window.scale_factor() as f32;
+                    graphics_context.canvas.set_size(size.width, size.height, scale_factor);
+                    app.camera.update(app.world.particles(), size.width as f32, size.height as f32);
 
                     app.draw_world(&mut graphics_context.canvas);
-
-                    if let Some(avg_duration) = app.consume_world_draw_average_time() {
-                        println!("Average draw time: {} milliseconds", avg_duration.as_millis());
-                    }
+                    app.update_stats();
+                    let viewport = app.camera.viewport(size.width as f32, size.height as f32);
+                    let dragged_box = box_start.map(|start| (start, cursor_position));
+                    app.selection.draw(&mut graphics_context.canvas, app.world.particles(), &viewport, dragged_box, scale_factor);
+                    minimap.draw(&mut graphics_context.canvas, app.world.particles(), &viewport, scale_factor);
+                    hud.draw(&mut graphics_context.canvas, &app, scale_factor);
 
                     graphics_context.surface.present(&mut graphics_context.canvas).expect("Could not preset canvas to screen");
                 },
-                WindowEvent::MouseWheel { phase, delta,  .. } => match (phase, delta) {
-                    (TouchPhase::Moved, MouseScrollDelta::LineDelta(_, input)) => {
-                        if input < 0. {
-                            app.update_camera_zoom(CameraZoomRequest::Out);
-                        } else if input > 0. {
-                            app.update_camera_zoom(CameraZoomRequest::In);
+                WindowEvent::CursorMoved { position, .. } => {
+                    cursor_position = Point::new(position.x as f32, position.y as f32);
+                    if app.camera.is_dragging() {
+                        app.camera.drag_to(cursor_position);
+                    }
+                },
+                WindowEvent::Focused(false) => app.camera.release_all(),
+                WindowEvent::ModifiersChanged(new_modifiers) => modifiers = new_modifiers.state(),
+                WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => {
+                    let size = graphics_context.window.inner_size();
+                    let viewport = app.camera.viewport(size.width as f32, size.height as f32);
+                    let scale_factor = graphics_context.window.scale_factor() as f32;
+
+                    if state == ElementState::Released {
+                        app.camera.end_drag();
+                        if let Some(start) = box_start.take() {
+                            app.selection.select_box(app.world.particles(), &viewport, start, cursor_position);
+                        } else if press_position.take().is_some_and(|p| (p - cursor_position).length() <= constants::CLICK_TOLERANCE * scale_factor) {
+                            app.selection.inspect_at(app.world.particles(), &viewport, cursor_position, scale_factor);
                         }
+                    } else if let Some(position) = minimap.world_position_at(cursor_position) {
+                        app.camera.center_on(position, size.width as f32, size.height as f32);
+                    } else if modifiers.shift_key() {
+                        box_start = Some(cursor_position);
+                    } else {
+                        press_position = Some(cursor_position);
+                        app.camera.start_drag(cursor_position);
                     }
-                    _ => {}
+                },
+                WindowEvent::MouseWheel { delta, .. } => {
+                    let steps = match delta {
+                        MouseScrollDelta::LineDelta(_, y) => y,
+                        MouseScrollDelta::PixelDelta(position) => position.y as f32 / constants::PIXELS_PER_ZOOM_STEP,
+                    };
+                    app.camera.zoom_at(cursor_position, steps);
                 },
                 WindowEvent::KeyboardInput {
                     event: winit::event::KeyEvent {
                         physical_key: winit::keyboard::PhysicalKey::Code(key),
-                        state: ElementState::Pressed,
+                        state,
                         ..
                     },
                     ..
                 } => {
-                    if let Ok(req) = key.try_into() {
-                        app.update_camera_position(req)
-                    } else if key == KeyCode::Digit1 {
-                        app.world.set_forces_config(ForcesConfig::random(-0.3, 1.0));
-                        app.world.accelerate_all(50.0);
-                    } else if key == KeyCode::Digit2 {
-                        app.world.set_forces_config(app.default_forces_config);
+                    if let Ok(req) = CameraMoveRequest::try_from(key) {
+                        app.camera.set_moving(req, state == ElementState::Pressed);
+                    } else if state == ElementState::Pressed {
+                        if key == KeyCode::KeyF {
+                            let size = graphics_context.window.inner_size();
+                            app.camera.fit_world(size.width as f32, size.height as f32);
+                        } else if key == KeyCode::KeyG {
+                            if app.camera.is_following() {
+                                app.camera.stop_following();
+                            } else {
+                                let size = graphics_context.window.inner_size();
+                                let position = app.camera.viewport(size.width as f32, size.height as f32).screen_to_world(cursor_position);
+                                app.follow_nearest(position, modifiers.shift_key());
+                            }
+                        } else if key == KeyCode::KeyM {
+                            minimap.toggle();
+                        } else if let Ok(req) = HudRequest::try_from(key) {
+                            hud.update(req);
+                        } else if let Ok(req) = VisualRequest::try_from(key) {
+                            app.update_visuals(req);
+                        } else if app.is_replaying() {
+                            if let Ok(req) = ReplayRequest::try_from(key) {
+                                app.update_replay(req);
+                            }
+                        } else if let Ok(req) = SelectionRequest::try_from(key) {
+                            app.selection.update(&mut app.world, req);
+                        } else if key == KeyCode::Digit1 {
+                            app.world.set_forces_config(ForcesConfig::random(-0.3, 1.0));
+                            app.world.accelerate_all(50.0);
+                        } else if key == KeyCode::Digit2 {
+                            app.world.set_forces_config(app.default_forces_config);
+                        }
                     }
                 }
                 _ => {}
-            },
-            _ => {}
+            }
         })?;
 
     Ok(())
 }
 
+#[allow(dead_code)]
 fn get_real_world() -> World {
-    let mut particles = Vec::new();
-
-    particles.push(Particle::new(Point::new(500., 500.), Vector::new(0., 0.), ParticleColor::Blue));
-    particles.push(Particle::new(Point::new(500., 400.), Vector::new(3., 1.), ParticleColor::Red));
+    let particles = vec![
+        Particle::new(Point::new(500., 500.), Vector::new(0., 0.), ParticleColor::Blue),
+        Particle::new(Point::new(500., 400.), Vector::new(3., 1.), ParticleColor::Red),
+    ];
 
     let forces = ForcesConfig::empty()
         .with_force(ParticleColor::Red, ParticleColor::Blue, 10.)
@@ -0,0 +1,119 @@
+use femtovg::{Canvas, Color, Paint, Path};
+use sim_lib::{Particle, Point, WORLD_HEIGHT_FLOAT, WORLD_WIDTH_FLOAT};
+use sim_render::Viewport;
+
+// Minimap width in logical pixels, the height follows the world aspect ratio
+const WIDTH: f32 = 240.;
+const MARGIN: f32 = 10.;
+// Size of a density cell in world units
+const CELL_SIZE: f32 = 50.;
+// Densities are shaded in this many steps, one fill call each
+const SHADES: usize = 8;
+const BACKGROUND_OPACITY: f32 = 0.7;
+const BORDER_COLOR: Color = Color::rgbf(0.5, 0.5, 0.5);
+const VIEWPORT_COLOR: Color = Color::rgbf(1., 1., 0.);
+
+// Bottom right corner overlay showing the whole world and where the camera is looking
+#[derive(Default)]
+pub struct Minimap {
+    hidden: bool,
+    // Where it was last drawn in screen pixels, used for clicks
+    bounds: Option<Rect>,
+}
+
+#[derive(Copy, Clone)]
+struct Rect {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+impl Minimap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn toggle(&mut self) {
+        self.hidden = !self.hidden;
+    }
+
+    // The world position under a screen position, if it is on the minimap
+    pub fn world_position_at(&self, screen_position: Point) -> Option<Point> {
+        let bounds = self.bounds.filter(|_| !self.hidden)?;
+        let (x, y) = ((screen_position.x - bounds.x) / bounds.width, (screen_position.y - bounds.y) / bounds.height);
+        if !(0. ..=1.).contains(&x) || !(0. ..=1.).contains(&y) {
+            return None;
+        }
+        Some(Point::new(x * WORLD_WIDTH_FLOAT, y * WORLD_HEIGHT_FLOAT))
+    }
+
+    // `scale` is the window scale factor, the canvas is in physical pixels
+    pub fn draw<R: femtovg::Renderer>(&mut self, canvas: &mut Canvas<R>, particles: &[Particle], viewport: &Viewport, scale: f32) {
+        if self.hidden {
+            return;
+        }
+
+        let width = WIDTH * scale;
+        let height = width * WORLD_HEIGHT_FLOAT / WORLD_WIDTH_FLOAT;
+        let bounds = Rect {
+            x: canvas.width() as f32 - width - MARGIN * scale,
+            y: canvas.height() as f32 - height - MARGIN * scale,
+            width,
+            height,
+        };
+        self.bounds = Some(bounds);
+        let to_minimap = |position: Point| Point::new(
+            bounds.x + position.x / WORLD_WIDTH_FLOAT * bounds.width,
+            bounds.y + position.y / WORLD_HEIGHT_FLOAT * bounds.height,
+        );
+
+        let mut background = Path::new();
+        background.rect(bounds.x, bounds.y, bounds.width, bounds.height);
+        canvas.fill_path(&background, &Paint::color(Color::rgbaf(0., 0., 0., BACKGROUND_OPACITY)));
+
+        let (columns, rows) = ((WORLD_WIDTH_FLOAT / CELL_SIZE).ceil() as usize, (WORLD_HEIGHT_FLOAT / CELL_SIZE).ceil() as usize);
+        let densities = density_grid(particles, columns, rows);
+        let max_density = densities.iter().copied().max().unwrap_or(0).max(1);
+        let (cell_width, cell_height) = (bounds.width / columns as f32, bounds.height / rows as f32);
+
+        let mut shades = vec![Path::new(); SHADES];
+        for (i, &density) in densities.iter().enumerate() {
+            if density == 0 {
+                continue;
+            }
+            // Square root so sparse areas still show up next to dense clusters
+            let shade = ((density as f32 / max_density as f32).sqrt() * (SHADES - 1) as f32).round() as usize;
+            let (column, row) = (i % columns, i / columns);
+            shades[shade].rect(bounds.x + column as f32 * cell_width, bounds.y + row as f32 * cell_height, cell_width, cell_height);
+        }
+        for (shade, path) in shades.iter().enumerate() {
+            let intensity = (shade + 1) as f32 / SHADES as f32;
+            canvas.fill_path(path, &Paint::color(Color::rgbaf(1., 1., 1., intensity)).with_anti_alias(false));
+        }
+
+        canvas.stroke_path(&background, &Paint::color(BORDER_COLOR).with_line_width(scale));
+
+        let top_left = to_minimap(viewport.screen_to_world(Point::new(0., 0.)));
+        let bottom_right = to_minimap(viewport.screen_to_world(Point::new(viewport.width, viewport.height)));
+        let mut camera = Path::new();
+        camera.rect(top_left.x, top_left.y, bottom_right.x - top_left.x, bottom_right.y - top_left.y);
+        canvas.save();
+        canvas.scissor(bounds.x, bounds.y, bounds.width, bounds.height);
+        canvas.stroke_path(&camera, &Paint::color(VIEWPORT_COLOR).with_line_width(scale));
+        canvas.restore();
+    }
+}
+
+// Row major particle counts, particles outside the world are left out
+fn density_grid(particles: &[Particle], columns: usize, rows: usize) -> Vec<u32> {
+    let mut densities = vec![0; columns * rows];
+    for particle in particles {
+        let position = particle.position();
+        let (column, row) = ((position.x / CELL_SIZE).floor(), (position.y / CELL_SIZE).floor());
+        if column >= 0. && row >= 0. && (column as usize) < columns && (row as usize) < rows {
+            densities[row as usize * columns + column as usize] += 1;
+        }
+    }
+    densities
+}
@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+const DEFAULT_RECORD_INTERVAL: u32 = 1;
+
+pub struct Options {
+    pub record_path: Option<PathBuf>,
+    pub record_interval: u32,
+    pub replay_path: Option<PathBuf>,
+    pub scenario_path: Option<PathBuf>,
+    pub script_path: Option<PathBuf>,
+}
+
+impl Options {
+    pub fn from_args() -> Result<Self, String> {
+        let mut options = Options {
+            record_path: None,
+            record_interval: DEFAULT_RECORD_INTERVAL,
+            replay_path: None,
+            scenario_path: None,
+            script_path: None,
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("Missing value for {}", arg));
+            match arg.as_str() {
+                "--record" => options.record_path = Some(value()?.into()),
+                "--record-every" => options.record_interval = value()?.parse().map_err(|e| format!("Invalid --record-every: {}", e))?,
+                "--replay" => options.replay_path = Some(value()?.into()),
+                "--scenario" => options.scenario_path = Some(value()?.into()),
+                "--script" => options.script_path = Some(value()?.into()),
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+
+        if options.record_path.is_some() && options.replay_path.is_some() {
+            return Err("--record and --replay cannot be used together".to_string());
+        }
+
+        if options.replay_path.is_some() && (options.scenario_path.is_some() || options.script_path.is_some()) {
+            return Err("--scenario and --script cannot be used with --replay".to_string());
+        }
+
+        Ok(options)
+    }
+}
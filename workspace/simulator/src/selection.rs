@@ -0,0 +1,186 @@
+use femtovg::{Canvas, Color, Paint, Path};
+use winit::keyboard::KeyCode;
+use sim_lib::{Particle, ParticleColor, Point, Scenario, World};
+use sim_render::{Viewport, BASE_PARTICLE_RADIUS};
+
+// Clicks within this many logical pixels of a particle pick it, even when it is drawn smaller
+const PICK_RADIUS: f32 = 6.;
+const RING_GAP: f32 = 2.;
+const SELECTED_COLOR: Color = Color::rgbf(1., 1., 1.);
+const PINNED_COLOR: Color = Color::rgbf(0., 0.9, 1.);
+const INSPECTED_COLOR: Color = Color::rgbf(1., 1., 0.);
+const BOX_FILL_OPACITY: f32 = 0.1;
+
+// The inspected particle and the box selected group, both by index into the world
+#[derive(Default)]
+pub struct Selection {
+    pub inspected: Option<usize>,
+    indices: Vec<usize>,
+    particles_count: usize,
+}
+
+pub enum SelectionRequest {
+    Recolor,
+    Delete,
+    TogglePin,
+    Export,
+    Clear,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    // Indices are meaningless once something else (a script, a replay) changes the particle count
+    pub fn sync(&mut self, particles_count: usize) {
+        if particles_count != self.particles_count {
+            *self = Selection { particles_count, ..Selection::default() };
+        }
+    }
+
+    // The particle drawn closest to a screen position, if it is close enough to count as clicked
+    pub fn particle_at(particles: &[Particle], viewport: &Viewport, screen_position: Point, scale: f32) -> Option<usize> {
+        let radius = f32::max(BASE_PARTICLE_RADIUS * viewport.scale, PICK_RADIUS * scale);
+        particles.iter()
+            .map(|p| (viewport.world_to_screen(p.position()) - screen_position).square_length())
+            .enumerate()
+            .filter(|&(_, distance)| distance <= radius * radius)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)
+    }
+
+    pub fn inspect_at(&mut self, particles: &[Particle], viewport: &Viewport, screen_position: Point, scale: f32) {
+        self.sync(particles.len());
+        self.inspected = Self::particle_at(particles, viewport, screen_position, scale);
+    }
+
+    // Replaces the selection with every particle drawn inside the screen rectangle spanned by two corners
+    pub fn select_box(&mut self, particles: &[Particle], viewport: &Viewport, from: Point, to: Point) {
+        self.sync(particles.len());
+        let (min, max) = (from.min(to), from.max(to));
+        self.indices = particles.iter().enumerate()
+            .filter(|(_, p)| {
+                let position = viewport.world_to_screen(p.position());
+                position.x >= min.x && position.x <= max.x && position.y >= min.y && position.y <= max.y
+            })
+            .map(|(i, _)| i)
+            .collect();
+        eprintln!("Selected {} particle(s)", self.indices.len());
+    }
+
+    pub fn update(&mut self, world: &mut World, request: SelectionRequest) {
+        self.sync(world.particles().len());
+        if self.indices.is_empty() && !matches!(request, SelectionRequest::Clear) {
+            return;
+        }
+
+        match request {
+            SelectionRequest::Recolor => {
+                let particles = world.particles_mut();
+                let current = particles[self.indices[0]].color();
+                let index = ParticleColor::ALL.iter().position(|&c| c == current).unwrap_or(0);
+                let color = ParticleColor::ALL[(index + 1) % ParticleColor::ALL.len()];
+                self.indices.iter().for_each(|&i| particles[i] = particles[i].with_color(color));
+            },
+            SelectionRequest::Delete => self.delete(world),
+            // Pinned particles keep exerting forces but stay where they are
+            SelectionRequest::TogglePin => {
+                let particles = world.particles_mut();
+                let pinned = !self.indices.iter().all(|&i| particles[i].is_pinned());
+                self.indices.iter().for_each(|&i| particles[i] = particles[i].with_pinned(pinned));
+            },
+            SelectionRequest::Export => {
+                let path = format!("selection-{}.scenario", world.ticks());
+                let scenario = Scenario::from_world(world).select_particles(&self.indices);
+                match scenario.save(&path) {
+                    Ok(()) => eprintln!("Exported {} particle(s) to {}", self.indices.len(), path),
+                    Err(e) => eprintln!("Could not export selection to {}: {}", path, e),
+                }
+            },
+            SelectionRequest::Clear => {
+                self.indices.clear();
+                self.inspected = None;
+            },
+        }
+    }
+
+    fn delete(&mut self, world: &mut World) {
+        let mut deleted = vec![false; world.particles().len()];
+        self.indices.iter().for_each(|&i| deleted[i] = true);
+
+        eprintln!("Deleted {} particle(s)", self.indices.len());
+        world.remove_particles(&deleted);
+        self.particles_count = world.particles().len();
+        self.indices.clear();
+        // Every deleted particle before the inspected one shifts it down by one
+        self.inspected = self.inspected.filter(|&i| !deleted[i]).map(|i| i - deleted[..i].iter().filter(|&&d| d).count());
+    }
+
+    // Rings around selected, pinned and inspected particles, plus the box being dragged if any.
+    // `scale` is the window scale factor, the canvas is in physical pixels
+    pub fn draw<R: femtovg::Renderer>(&self, canvas: &mut Canvas<R>, particles: &[Particle], viewport: &Viewport, dragged_box: Option<(Point, Point)>, scale: f32) {
+        if particles.len() == self.particles_count {
+            let radius = BASE_PARTICLE_RADIUS * viewport.scale + RING_GAP * scale;
+            let selected = self.indices.iter().copied();
+            let pinned = particles.iter().enumerate().filter(|(_, p)| p.is_pinned()).map(|(i, _)| i);
+
+            stroke_rings(canvas, particles, viewport, selected, radius, SELECTED_COLOR, scale);
+            stroke_rings(canvas, particles, viewport, pinned, radius + RING_GAP * scale, PINNED_COLOR, scale);
+            stroke_rings(canvas, particles, viewport, self.inspected.into_iter(), radius + RING_GAP * 2. * scale, INSPECTED_COLOR, scale);
+        }
+
+        if let Some((from, to)) = dragged_box {
+            let (min, max) = (from.min(to), from.max(to));
+            let mut path = Path::new();
+            path.rect(min.x, min.y, max.x - min.x, max.y - min.y);
+            canvas.fill_path(&path, &Paint::color(Color::rgbaf(1., 1., 1., BOX_FILL_OPACITY)));
+            canvas.stroke_path(&path, &Paint::color(SELECTED_COLOR).with_line_width(scale));
+        }
+    }
+}
+
+impl TryFrom<KeyCode> for SelectionRequest {
+    type Error = ();
+
+    fn try_from(key: KeyCode) -> Result<Self, Self::Error> {
+        Ok(match key {
+            KeyCode::KeyP => SelectionRequest::Recolor,
+            KeyCode::KeyX | KeyCode::Delete | KeyCode::Backspace => SelectionRequest::Delete,
+            KeyCode::KeyZ => SelectionRequest::TogglePin,
+            KeyCode::KeyE => SelectionRequest::Export,
+            KeyCode::Escape => SelectionRequest::Clear,
+            _ => return Err(())
+        })
+    }
+}
+
+// One stroke call per ring color
+fn stroke_rings<R: femtovg::Renderer>(
+    canvas: &mut Canvas<R>,
+    particles: &[Particle],
+    viewport: &Viewport,
+    indices: impl Iterator<Item = usize>,
+    radius: f32,
+    color: Color,
+    scale: f32,
+) {
+    let mut path = Path::new();
+    let mut empty = true;
+    for i in indices {
+        let position = viewport.world_to_screen(particles[i].position());
+        if position.x < -radius || position.y < -radius || position.x > viewport.width + radius || position.y > viewport.height + radius {
+            continue;
+        }
+        path.circle(position.x, position.y, radius);
+        empty = false;
+    }
+
+    if !empty {
+        canvas.stroke_path(&path, &Paint::color(color).with_line_width(scale));
+    }
+}
@@ -21,13 +21,18 @@ impl Timer {
         }
     }
 
-    pub fn start(&mut self) -> ActiveMeasurement {
+    pub fn start(&mut self) -> ActiveMeasurement<'_> {
         ActiveMeasurement {
             timer: self,
             start_time: std::time::Instant::now()
         }
     }
 
+    pub fn record(&mut self, duration: Duration) {
+        self.count += 1;
+        self.sum += duration;
+    }
+
     pub fn consume_average_time(&mut self) -> Option<Duration> {
         if self.count != 0 && self.count >= self.chunk_size_for_avg {
             let avg = self.sum.div(self.count);
@@ -42,7 +47,6 @@ impl Timer {
 
 impl ActiveMeasurement<'_> {
     pub fn end(self) {
-        self.timer.count += 1;
-        self.timer.sum += self.start_time.elapsed();
+        self.timer.record(self.start_time.elapsed());
     }
 }